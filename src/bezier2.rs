@@ -1,6 +1,6 @@
+use engine::graphics::DrawPrimitives;
 use entities::Renderable;
 use ggez::graphics::Point2;
-use ggez::Context;
 use math::VectorUtils;
 
 #[derive(Debug)]
@@ -52,11 +52,10 @@ impl Bezier {
 }
 
 impl Renderable for Bezier {
-    fn render(&mut self, ctx: &mut Context) {
-        use ggez::graphics;
+    fn render(&self, surface: &mut DrawPrimitives) {
         use palette::Palette;
 
-        graphics::set_color(ctx, Palette::DebugA.into()).unwrap();
+        surface.set_color(&Palette::DebugA);
 
         let precision = 10;
 
@@ -66,7 +65,7 @@ impl Renderable for Bezier {
             .map(|x| self.get(x as f32 / lines as f32))
             .collect::<Vec<Point2>>();
 
-        graphics::line(ctx, &v, 1.0).unwrap();
+        surface.polyline(&v, 1.0);
     }
 }
 
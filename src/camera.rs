@@ -0,0 +1,98 @@
+use engine::graphics::Transform;
+use ggez::graphics::Point2;
+use math::VectorUtils;
+use playfield;
+
+// Shake at full trauma, in pixels and radians.
+const MAX_SHAKE_OFFSET: f32 = 12.0;
+const MAX_SHAKE_ANGLE: f32 = 0.05;
+const SHAKE_FREQUENCY: f32 = 25.0;
+// Trauma lost per second.
+const TRAUMA_DECAY: f32 = 1.5;
+// Fraction of the distance to the target covered per second.
+const FOLLOW_SPEED: f32 = 3.0;
+
+/// Where the world is looked at from. `position` is the world point drawn at
+/// the center of the window.
+#[derive(Debug, Clone)]
+pub struct Camera {
+    pub position: Point2,
+    pub zoom: f32,
+    pub rotation: f32,
+    target: Option<Point2>,
+    trauma: f32,
+    time: f32,
+    moved: Point2,
+}
+
+impl Camera {
+    pub fn new() -> Self {
+        Self {
+            position: playfield::center(),
+            zoom: 1.0,
+            rotation: 0.0,
+            target: None,
+            trauma: 0.0,
+            time: 0.0,
+            moved: Point2::zero(),
+        }
+    }
+
+    // Glides towards `target` every update, `None` leaves the camera where it is.
+    pub fn follow(&mut self, target: Option<Point2>) {
+        self.target = target;
+    }
+
+    // Trauma goes from 0 to 1, the shake grows with its square.
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0).max(0.0);
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        let previous = self.position;
+        if let Some(target) = self.target {
+            let amount = 1.0 - (-FOLLOW_SPEED * delta_time).exp();
+            self.position = self.position.lerp(target, amount);
+        }
+        self.moved = self.position.sub(previous);
+        self.trauma = (self.trauma - TRAUMA_DECAY * delta_time).max(0.0);
+        self.time += delta_time;
+    }
+
+    // How far the camera moved in the last update, shake not included.
+    pub fn moved(&self) -> Point2 {
+        self.moved
+    }
+
+    pub fn transform(&self) -> Transform {
+        let shake = self.trauma * self.trauma;
+        let t = self.time * SHAKE_FREQUENCY;
+        let offset =
+            Point2::new((t * 1.1).sin(), (t * 1.7 + 1.0).sin()).mul(MAX_SHAKE_OFFSET * shake);
+        let angle = (t * 0.9 + 2.0).sin() * MAX_SHAKE_ANGLE * shake;
+        let center = playfield::center();
+        let position = self.position.add(offset);
+        Transform::translate(center.x, center.y)
+            .then(&Transform::rotate(self.rotation + angle))
+            .then(&Transform::scale(self.zoom, self.zoom))
+            .then(&Transform::translate(-position.x, -position.y))
+    }
+}
+
+#[test]
+fn camera_follows_and_settles() {
+    let mut camera = Camera::new();
+    let p = Point2::new(10.0, 20.0);
+    assert_eq!(camera.transform().apply(p), p);
+
+    camera.follow(Some(playfield::center().add(Point2::new(100.0, 0.0))));
+    camera.add_trauma(0.5);
+    camera.update(0.1);
+    assert!(camera.moved().x > 0.0 && camera.moved().x < 100.0);
+    for _ in 0..100 {
+        camera.update(0.1);
+    }
+    let settled = camera.transform().apply(p);
+    assert!((settled.x - (p.x - 100.0)).abs() < 0.01);
+    assert!((settled.y - p.y).abs() < 0.01);
+}
@@ -0,0 +1,270 @@
+//! Color math on top of the `[f32; 4]` RGBA colors the backends draw with.
+//! Channels go from 0 to 1, hues are in degrees.
+
+use engine::graphics::Color;
+use tween::Keyframes;
+
+pub type Rgba = [f32; 4];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hsv {
+    pub h: f32,
+    pub s: f32,
+    pub v: f32,
+    pub a: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hsl {
+    pub h: f32,
+    pub s: f32,
+    pub l: f32,
+    pub a: f32,
+}
+
+fn wrap_hue(hue: f32) -> f32 {
+    (hue % 360.0 + 360.0) % 360.0
+}
+
+// Hue, chroma, and the largest and smallest channel of an RGB color.
+fn hue_chroma(c: Rgba) -> (f32, f32, f32, f32) {
+    let max = c[0].max(c[1]).max(c[2]);
+    let min = c[0].min(c[1]).min(c[2]);
+    let chroma = max - min;
+    let hue = if chroma == 0.0 {
+        0.0
+    } else if max == c[0] {
+        60.0 * ((c[1] - c[2]) / chroma)
+    } else if max == c[1] {
+        60.0 * ((c[2] - c[0]) / chroma + 2.0)
+    } else {
+        60.0 * ((c[0] - c[1]) / chroma + 4.0)
+    };
+    (wrap_hue(hue), chroma, max, min)
+}
+
+// The RGB color with the given hue and chroma, before adding the lightness
+// shared by all channels.
+fn from_hue_chroma(hue: f32, chroma: f32) -> [f32; 3] {
+    let h = wrap_hue(hue) / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    match h as u32 {
+        0 => [chroma, x, 0.0],
+        1 => [x, chroma, 0.0],
+        2 => [0.0, chroma, x],
+        3 => [0.0, x, chroma],
+        4 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    }
+}
+
+impl Hsv {
+    pub fn new(h: f32, s: f32, v: f32) -> Self {
+        Hsv { h, s, v, a: 1.0 }
+    }
+
+    pub fn from_rgba(c: Rgba) -> Self {
+        let (h, chroma, max, _) = hue_chroma(c);
+        let s = if max == 0.0 { 0.0 } else { chroma / max };
+        Hsv {
+            h,
+            s,
+            v: max,
+            a: c[3],
+        }
+    }
+
+    pub fn to_rgba(&self) -> Rgba {
+        let chroma = self.v * self.s;
+        let [r, g, b] = from_hue_chroma(self.h, chroma);
+        let m = self.v - chroma;
+        [r + m, g + m, b + m, self.a]
+    }
+}
+
+impl Hsl {
+    pub fn new(h: f32, s: f32, l: f32) -> Self {
+        Hsl { h, s, l, a: 1.0 }
+    }
+
+    pub fn from_rgba(c: Rgba) -> Self {
+        let (h, chroma, max, min) = hue_chroma(c);
+        let l = (max + min) / 2.0;
+        let s = if chroma == 0.0 {
+            0.0
+        } else {
+            chroma / (1.0 - (2.0 * l - 1.0).abs())
+        };
+        Hsl { h, s, l, a: c[3] }
+    }
+
+    pub fn to_rgba(&self) -> Rgba {
+        let chroma = (1.0 - (2.0 * self.l - 1.0).abs()) * self.s;
+        let [r, g, b] = from_hue_chroma(self.h, chroma);
+        let m = self.l - chroma / 2.0;
+        [r + m, g + m, b + m, self.a]
+    }
+}
+
+impl Color for Hsv {
+    fn into_rgba(&self) -> [f32; 4] {
+        self.to_rgba()
+    }
+}
+
+impl Color for Hsl {
+    fn into_rgba(&self) -> [f32; 4] {
+        self.to_rgba()
+    }
+}
+
+// sRGB channel to linear light and back.
+pub fn to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn from_linear(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// Oklab, where equal steps look like equal changes in color.
+fn to_oklab(c: Rgba) -> [f32; 3] {
+    let (r, g, b) = (to_linear(c[0]), to_linear(c[1]), to_linear(c[2]));
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+    [
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    ]
+}
+
+fn from_oklab(lab: [f32; 3], alpha: f32) -> Rgba {
+    let l = (lab[0] + 0.3963377774 * lab[1] + 0.2158037573 * lab[2]).powi(3);
+    let m = (lab[0] - 0.1055613458 * lab[1] - 0.0638541728 * lab[2]).powi(3);
+    let s = (lab[0] - 0.0894841775 * lab[1] - 1.2914855480 * lab[2]).powi(3);
+    let channel = |c: f32| from_linear(c).min(1.0).max(0.0);
+    [
+        channel(4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s),
+        channel(-1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s),
+        channel(-0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s),
+        alpha,
+    ]
+}
+
+// Goes from `a` at t = 0 to `b` at t = 1 through evenly spaced looking colors.
+pub fn lerp(a: Rgba, b: Rgba, t: f32) -> Rgba {
+    let (la, lb) = (to_oklab(a), to_oklab(b));
+    let mix = |x: f32, y: f32| x + (y - x) * t;
+    from_oklab(
+        [mix(la[0], lb[0]), mix(la[1], lb[1]), mix(la[2], lb[2])],
+        mix(a[3], b[3]),
+    )
+}
+
+pub fn premultiply(c: Rgba) -> Rgba {
+    [c[0] * c[3], c[1] * c[3], c[2] * c[3], c[3]]
+}
+
+pub fn unpremultiply(c: Rgba) -> Rgba {
+    if c[3] == 0.0 {
+        [0.0, 0.0, 0.0, 0.0]
+    } else {
+        [c[0] / c[3], c[1] / c[3], c[2] / c[3], c[3]]
+    }
+}
+
+// Draws `src` over `dst`, both premultiplied.
+pub fn blend(src: Rgba, dst: Rgba) -> Rgba {
+    let k = 1.0 - src[3];
+    [
+        src[0] + dst[0] * k,
+        src[1] + dst[1] * k,
+        src[2] + dst[2] * k,
+        src[3] + dst[3] * k,
+    ]
+}
+
+/// Colors placed along [0, 1], sampled in between with `lerp`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    stops: Keyframes<Rgba>,
+}
+
+impl Gradient {
+    pub fn new(stops: Vec<(f32, Rgba)>) -> Self {
+        Gradient {
+            stops: Keyframes::new(stops),
+        }
+    }
+
+    // Stops spread evenly from 0 to 1.
+    pub fn even(colors: &[Rgba]) -> Self {
+        let last = (colors.len() as f32 - 1.0).max(1.0);
+        Gradient::new(
+            colors
+                .iter()
+                .enumerate()
+                .map(|(i, c)| (i as f32 / last, *c))
+                .collect(),
+        )
+    }
+
+    pub fn sample(&self, t: f32) -> Rgba {
+        self.stops.sample(t)
+    }
+}
+
+#[cfg(test)]
+fn close(a: Rgba, b: Rgba) -> bool {
+    a.iter().zip(b.iter()).all(|(x, y)| (x - y).abs() < 0.002)
+}
+
+#[test]
+fn color_spaces_round_trip() {
+    let orange = [0.9, 0.6, 0.0, 0.5];
+    let hsv = Hsv::from_rgba(orange);
+    assert!((hsv.h - 40.0).abs() < 0.01);
+    assert!(close(hsv.to_rgba(), orange));
+    assert!(close(Hsl::from_rgba(orange).to_rgba(), orange));
+    assert!(close(
+        Hsl::new(240.0, 1.0, 0.5).to_rgba(),
+        [0.0, 0.0, 1.0, 1.0]
+    ));
+    let gray = [0.5, 0.5, 0.5, 1.0];
+    assert!(close(lerp(gray, [0.9, 0.2, 0.4, 1.0], 0.0), gray));
+}
+
+#[test]
+fn gradients_and_blending() {
+    let black = [0.0, 0.0, 0.0, 1.0];
+    let white = [1.0, 1.0, 1.0, 1.0];
+    let gradient = Gradient::even(&[black, white, black]);
+    assert!(close(gradient.sample(-1.0), black));
+    assert!(close(gradient.sample(0.5), white));
+    // Halfway in Oklab, a lot darker than half the light of white.
+    let gray = gradient.sample(0.25)[0];
+    assert!(gray > 0.35 && gray < 0.45);
+    let edge = Gradient::new(vec![(0.0, black), (0.5, black), (0.5, white), (1.0, white)]);
+    assert!(close(edge.sample(0.4), black));
+    assert!(close(edge.sample(0.6), white));
+
+    let red = premultiply([1.0, 0.0, 0.0, 0.5]);
+    let blended = unpremultiply(blend(red, white));
+    assert!(close(blended, [1.0, 0.5, 0.5, 1.0]));
+}
+
+#[test]
+#[should_panic]
+fn gradients_reject_nan_stops() {
+    Gradient::new(vec![(0.0, [0.0; 4]), (::std::f32::NAN, [1.0; 4])]);
+}
@@ -1,13 +1,26 @@
 use entities::{EntityTag, EntityTagPlayer};
-use ggez::event::{Keycode, Mod};
+use ggez::event::{Button, Keycode};
 use ggez::graphics::Point2;
 use math::VectorUtils;
 use messages::{Direction, Message, SendMessageTo};
+use settings::Bindings;
 use Game;
 
+use std::collections::HashMap;
+
+// How much faster cutscenes play while fast forwarding.
+const FAST_FORWARD: f32 = 4.0;
+
 pub struct Controller {
     p1_axis: Point2,
     p1_motion_axis: Point2,
+    // Where each held key or button pushes, taken back on release. Keys
+    // pressed while a menu was open are never held.
+    held_keys: HashMap<Keycode, Point2>,
+    held_buttons: HashMap<Button, Point2>,
+    skip: bool,
+    // Held to shoot, and to fast forward cutscenes.
+    action: bool,
 }
 
 impl Controller {
@@ -15,6 +28,10 @@ impl Controller {
         Controller {
             p1_axis: Point2::zero(),
             p1_motion_axis: Point2::zero(),
+            held_keys: HashMap::new(),
+            held_buttons: HashMap::new(),
+            skip: false,
+            action: false,
         }
     }
     pub fn update(&mut self, game: &mut Game) {
@@ -26,23 +43,70 @@ impl Controller {
                 Message::Move(Direction::Point(self.p1_motion_axis), 5.0),
             );
         }
+        if self.action {
+            game.send_message(EntityTag::Player(EntityTagPlayer::Both), Message::Shoot);
+        }
     }
-    pub fn key_down_event(&mut self, _game: &mut Game, keycode: Keycode, _keymod: Mod) {
+    pub fn key_down_event(&mut self, game: &mut Game, keycode: Keycode) {
         println!("keycode {:?} down", keycode);
-        let p1_axis = self.p1_axis_direction(keycode);
+        match keycode {
+            Keycode::Escape => self.skip = true,
+            Keycode::Space => self.action = true,
+            _ => (),
+        }
+        let p1_axis = self.p1_axis_direction(game.settings.bindings, keycode);
         self.p1_axis = self.p1_axis.add(p1_axis);
+        self.held_keys.insert(keycode, p1_axis);
     }
-    pub fn key_up_event(&mut self, _game: &mut Game, keycode: Keycode, _keymod: Mod) {
+    pub fn key_up_event(&mut self, _game: &mut Game, keycode: Keycode) {
         println!("keycode {:?} up", keycode);
-        let p1_axis = self.p1_axis_direction(keycode);
-        self.p1_axis = self.p1_axis.sub(p1_axis);
+        if keycode == Keycode::Space {
+            self.action = false;
+        }
+        if let Some(p1_axis) = self.held_keys.remove(&keycode) {
+            self.p1_axis = self.p1_axis.sub(p1_axis);
+        }
     }
-    fn p1_axis_direction(&self, keycode: Keycode) -> Point2 {
-        match keycode {
-            Keycode::A => Point2::left(),
-            Keycode::S => Point2::down(),
-            Keycode::D => Point2::right(),
-            Keycode::W => Point2::up(),
+    pub fn button_down_event(&mut self, _game: &mut Game, button: Button) {
+        match button {
+            Button::Start => self.skip = true,
+            Button::A => self.action = true,
+            _ => (),
+        }
+        let p1_axis = match button {
+            Button::DPadLeft => Point2::left(),
+            Button::DPadDown => Point2::down(),
+            Button::DPadRight => Point2::right(),
+            Button::DPadUp => Point2::up(),
+            _ => Point2::zero(),
+        };
+        self.p1_axis = self.p1_axis.add(p1_axis);
+        self.held_buttons.insert(button, p1_axis);
+    }
+    pub fn button_up_event(&mut self, _game: &mut Game, button: Button) {
+        if button == Button::A {
+            self.action = false;
+        }
+        if let Some(p1_axis) = self.held_buttons.remove(&button) {
+            self.p1_axis = self.p1_axis.sub(p1_axis);
+        }
+    }
+    // Whether skipping the cutscene was asked for since the last call.
+    pub fn take_skip(&mut self) -> bool {
+        ::std::mem::replace(&mut self.skip, false)
+    }
+    pub fn cutscene_speed(&self) -> f32 {
+        match self.action {
+            true => FAST_FORWARD,
+            false => 1.0,
+        }
+    }
+    fn p1_axis_direction(&self, bindings: Bindings, keycode: Keycode) -> Point2 {
+        match (bindings, keycode) {
+            (Bindings::Wasd, Keycode::A) | (Bindings::Arrows, Keycode::Left) => Point2::left(),
+            (Bindings::Wasd, Keycode::S) | (Bindings::Arrows, Keycode::Down) => Point2::down(),
+            (Bindings::Wasd, Keycode::D) | (Bindings::Arrows, Keycode::Right) => Point2::right(),
+            (Bindings::Wasd, Keycode::W) | (Bindings::Arrows, Keycode::Up) => Point2::up(),
             _ => Point2::zero(),
         }
     }
@@ -0,0 +1,415 @@
+//! Cutscenes as timelines: tracks of actions that play side by side.
+//!
+//! Every line of a cutscene file is a cue: the seconds to wait after the
+//! previous cue of its track, the action and its arguments. `track <name>`
+//! starts a new track. Positions are fractions of the playfield, `a/b`
+//! fractions are fine. Blank lines and lines starting with `#` are ignored.
+//!
+//! ```text
+//! track story
+//! 1.0 spawn mother mother 0.5 1/3
+//! 0.5 tween mother 0.5 1/2 2 inout-quad
+//! 0 wait arrived mother
+//! 1.0 send mother kill
+//! 0 blink 0.5
+//! ```
+
+use ggez::graphics::Point2;
+
+use entities::blink::Blink;
+use entities::intro::{MegaRay, MotherIntro, TwinIntro};
+use entities::stars::Stars;
+use entities::{Entity, EntityId};
+use mekano_format::FormatError;
+use messages::{Direction, Message, SendMessageTo};
+use playfield;
+use tween::{Ease, Family, Tween};
+use Game;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+#[derive(Clone, Debug)]
+pub enum Prefab {
+    Stars(f32),
+    Mother(Point2),
+    MegaRay(Point2),
+    Twin(Point2),
+}
+
+impl Prefab {
+    fn build(&self) -> Box<Entity> {
+        match *self {
+            Prefab::Stars(distance) => Box::new(Stars::new(distance)),
+            Prefab::Mother(pos) => Box::new(MotherIntro::new(pos)),
+            Prefab::MegaRay(pos) => Box::new(MegaRay::new(pos)),
+            Prefab::Twin(pos) => Box::new(TwinIntro::new(pos)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Condition {
+    // The actor died or was never spawned.
+    Gone(String),
+    // The actor has no tween left to play.
+    Arrived(String),
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    // Actors spawned without a name can't be talked to later.
+    Spawn(Option<String>, Prefab),
+    Send(String, Message),
+    Tween(String, Point2, f32, Ease),
+    Blink(f32),
+    Wait(Condition),
+}
+
+#[derive(Clone, Debug)]
+pub struct Cue {
+    pub delay: f32,
+    pub action: Action,
+}
+
+#[derive(Clone, Debug)]
+struct Track {
+    cues: Vec<Cue>,
+    next: usize,
+    waited: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Timeline {
+    tracks: Vec<Track>,
+    actors: HashMap<String, EntityId>,
+    // Everything spawned, named or not, so it can be torn down. Blinks fade
+    // out on their own, the last one often outlives the cutscene.
+    spawned: Vec<EntityId>,
+    tweens: Vec<(EntityId, Tween<Point2>)>,
+}
+
+impl Timeline {
+    pub fn new(tracks: Vec<Vec<Cue>>) -> Self {
+        Timeline {
+            tracks: tracks
+                .into_iter()
+                .map(|cues| Track {
+                    cues,
+                    next: 0,
+                    waited: 0.0,
+                })
+                .collect(),
+            actors: HashMap::new(),
+            spawned: Vec::new(),
+            tweens: Vec::new(),
+        }
+    }
+
+    pub fn intro() -> Self {
+        Timeline::parse(include_str!("../cutscenes/intro.cutscene"))
+            .expect("the intro cutscene is valid")
+    }
+
+    pub fn load(path: &Path) -> Result<Timeline, FormatError> {
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+        Timeline::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Timeline, FormatError> {
+        let mut tracks: Vec<Vec<Cue>> = Vec::new();
+        for (i, raw) in text.lines().enumerate() {
+            let number = i + 1;
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words[0] == "track" {
+                tracks.push(Vec::new());
+                continue;
+            }
+            let cue = parse_cue(&words).map_err(|e| FormatError::Syntax(number, e))?;
+            match tracks.last_mut() {
+                Some(track) => track.push(cue),
+                None => {
+                    return Err(FormatError::Syntax(
+                        number,
+                        "cues go inside a track".to_string(),
+                    ))
+                }
+            }
+        }
+        Ok(Timeline::new(tracks))
+    }
+
+    pub fn finished(&self) -> bool {
+        self.tweens.is_empty() && self.tracks.iter().all(|t| t.next == t.cues.len())
+    }
+
+    // Jumps to the end: every cue left is performed at once, except for
+    // blinks and waits, and tweens land where they were going.
+    pub fn skip(&mut self, game: &mut Game) {
+        for i in 0..self.tracks.len() {
+            let next = self.tracks[i].next;
+            let cues: Vec<Cue> = self.tracks[i].cues[next..].to_vec();
+            for cue in cues {
+                match cue.action {
+                    Action::Blink(_) | Action::Wait(_) => (),
+                    ref action => self.perform(action, game),
+                }
+            }
+            self.tracks[i].next = self.tracks[i].cues.len();
+        }
+        for (id, mut tween) in self.tweens.drain(..) {
+            game.set_position(id, tween.finish());
+        }
+    }
+
+    // Removes whatever the cutscene spawned and is still around.
+    pub fn teardown(&mut self, game: &mut Game) {
+        for id in self.spawned.drain(..) {
+            game.remove_entity(id);
+        }
+        self.tweens.clear();
+    }
+
+    pub fn update(&mut self, game: &mut Game) {
+        let delta_time = game.delta_time();
+        for i in 0..self.tracks.len() {
+            self.tracks[i].waited += delta_time;
+            while let Some(cue) = self.tracks[i].cues.get(self.tracks[i].next).cloned() {
+                if self.tracks[i].waited < cue.delay {
+                    break;
+                }
+                if let Action::Wait(ref condition) = cue.action {
+                    if !self.holds(condition, game) {
+                        break;
+                    }
+                    self.tracks[i].waited = 0.0;
+                } else {
+                    self.tracks[i].waited -= cue.delay;
+                    self.perform(&cue.action, game);
+                }
+                self.tracks[i].next += 1;
+            }
+        }
+
+        for &mut (id, ref mut tween) in self.tweens.iter_mut() {
+            game.set_position(id, tween.update(delta_time));
+        }
+        self.tweens.retain(|&(_, ref tween)| !tween.finished());
+    }
+
+    fn perform(&mut self, action: &Action, game: &mut Game) {
+        match *action {
+            Action::Spawn(ref name, ref prefab) => {
+                let id = game.add_entity(prefab.build());
+                self.spawned.push(id);
+                if let Some(ref name) = *name {
+                    self.actors.insert(name.clone(), id);
+                }
+            }
+            Action::Send(ref name, message) => {
+                if let Some(&id) = self.actors.get(name) {
+                    game.send_message(id, message);
+                }
+            }
+            Action::Tween(ref name, to, duration, ease) => {
+                let actor = self.actors.get(name).cloned();
+                let from = actor.and_then(|id| game.position_of(id));
+                if let (Some(id), Some(from)) = (actor, from) {
+                    self.tweens.retain(|&(other, _)| other != id);
+                    self.tweens
+                        .push((id, Tween::new(from).to(to, duration, ease)));
+                }
+            }
+            Action::Blink(time) => {
+                game.add_entity(Box::new(Blink::new(time)));
+            }
+            Action::Wait(_) => (),
+        }
+    }
+
+    fn holds(&self, condition: &Condition, game: &Game) -> bool {
+        match *condition {
+            Condition::Gone(ref name) => match self.actors.get(name) {
+                Some(&id) => game.position_of(id).is_none(),
+                None => true,
+            },
+            Condition::Arrived(ref name) => match self.actors.get(name) {
+                Some(&id) => self.tweens.iter().all(|&(other, _)| other != id),
+                None => true,
+            },
+        }
+    }
+}
+
+fn parse_cue(words: &[&str]) -> Result<Cue, String> {
+    let delay = number(words[0])?;
+    let arg = |i: usize| {
+        words
+            .get(i)
+            .cloned()
+            .ok_or_else(|| format!("missing arguments in {}", words.join(" ")))
+    };
+    let point = |i: usize| -> Result<Point2, String> {
+        Ok(playfield::at(number(arg(i)?)?, number(arg(i + 1)?)?))
+    };
+    let action = match arg(1)? {
+        "spawn" => {
+            let name = match arg(2)? {
+                "-" => None,
+                name => Some(name.to_string()),
+            };
+            let prefab = match arg(3)? {
+                "stars" => Prefab::Stars(number(arg(4)?)?),
+                "mother" => Prefab::Mother(point(4)?),
+                "mega_ray" => Prefab::MegaRay(point(4)?),
+                "twin" => Prefab::Twin(point(4)?),
+                kind => return Err(format!("unknown entity {}", kind)),
+            };
+            Action::Spawn(name, prefab)
+        }
+        "send" => Action::Send(arg(2)?.to_string(), message(&words[3..])?),
+        "tween" => {
+            let duration = number(arg(5)?)?;
+            // Zero moves the actor at once.
+            if !(duration >= 0.0) {
+                return Err(format!("bad tween duration {}", duration));
+            }
+            Action::Tween(arg(2)?.to_string(), point(3)?, duration, ease(arg(6)?)?)
+        }
+        "blink" => {
+            let time = number(arg(2)?)?;
+            if !(time > 0.0) {
+                return Err(format!("bad blink time {}", time));
+            }
+            Action::Blink(time)
+        }
+        "wait" => Action::Wait(match arg(2)? {
+            "gone" => Condition::Gone(arg(3)?.to_string()),
+            "arrived" => Condition::Arrived(arg(3)?.to_string()),
+            condition => return Err(format!("unknown condition {}", condition)),
+        }),
+        action => return Err(format!("unknown action {}", action)),
+    };
+    Ok(Cue { delay, action })
+}
+
+// A number, or a fraction like `5/6`.
+fn number(word: &str) -> Result<f32, String> {
+    let parse = |w: &str| w.parse::<f32>().map_err(|_| format!("bad number {}", word));
+    match word.find('/') {
+        Some(at) => Ok(parse(&word[..at])? / parse(&word[at + 1..])?),
+        None => parse(word),
+    }
+}
+
+fn message(words: &[&str]) -> Result<Message, String> {
+    match words {
+        ["kill"] => Ok(Message::Kill),
+        ["start"] => Ok(Message::Start),
+        ["stop"] => Ok(Message::Stop),
+        ["shoot"] => Ok(Message::Shoot),
+        ["move", direction, amount] => {
+            let direction = match *direction {
+                "up" => Direction::Up,
+                "down" => Direction::Down,
+                "left" => Direction::Left,
+                "right" => Direction::Right,
+                _ => return Err(format!("unknown direction {}", direction)),
+            };
+            Ok(Message::Move(direction, number(amount)?))
+        }
+        _ => Err(format!("unknown message {}", words.join(" "))),
+    }
+}
+
+// `linear`, or `in`, `out` or `inout` and a family, as in `inout-quad`.
+fn ease(word: &str) -> Result<Ease, String> {
+    if word == "linear" {
+        return Ok(Ease::Linear);
+    }
+    let mut parts = word.splitn(2, '-');
+    let kind = parts.next().unwrap_or("");
+    let family = match parts.next() {
+        Some("quad") => Family::Quad,
+        Some("cubic") => Family::Cubic,
+        Some("elastic") => Family::Elastic,
+        Some("back") => Family::Back,
+        Some("bounce") => Family::Bounce,
+        _ => return Err(format!("unknown easing {}", word)),
+    };
+    match kind {
+        "in" => Ok(Ease::In(family)),
+        "out" => Ok(Ease::Out(family)),
+        "inout" => Ok(Ease::InOut(family)),
+        _ => Err(format!("unknown easing {}", word)),
+    }
+}
+
+#[test]
+fn timeline_waits_for_conditions_and_skips() {
+    use entities::EntityTag;
+
+    let mut game = Game::new();
+    let text = "track a\n\
+         0 spawn twin twin 0 0\n\
+         0 tween twin 1 0 0.1 linear\n\
+         0 wait arrived twin\n\
+         0 send twin kill\n\
+         track b\n\
+         0.2 blink 1/2\n";
+    let mut timeline = Timeline::parse(text).unwrap();
+    timeline.update(&mut game);
+    let twin = timeline.actors["twin"];
+    assert!(game.position_of(twin).is_some());
+    for _ in 0..10 {
+        timeline.update(&mut game);
+        game.update();
+    }
+    assert!(game.position_of(twin).is_none());
+    assert!(timeline.finished());
+    assert!(Timeline::parse("0 blink 1").is_err());
+
+    let mut intro = Timeline::intro();
+    intro.update(&mut game);
+    intro.skip(&mut game);
+    assert!(intro.finished());
+    assert!(game.position_of(intro.actors["mother"]).is_some());
+    game.update();
+    assert!(game.position_of(intro.actors["mother"]).is_none());
+    assert_eq!(game.positions(EntityTag::Stars).len(), 2);
+    intro.teardown(&mut game);
+    assert!(game.positions(EntityTag::Stars).is_empty());
+
+    let mut game = Game::new();
+    let mut flash = Timeline::parse("track a\n0 blink 1").unwrap();
+    flash.update(&mut game);
+    assert!(flash.finished());
+    flash.teardown(&mut game);
+    assert_eq!(game.positions(EntityTag::Untagged).len(), 1);
+    assert!(Timeline::parse("track a\n0 dance").is_err());
+    assert!(Timeline::parse("track a\n0").is_err());
+    assert!(Timeline::parse("track a\n0 blink 0").is_err());
+    assert!(Timeline::parse("track a\n0 tween a 0 0 -1 linear").is_err());
+}
+
+#[test]
+fn time_scale_speeds_up_cues_and_entities() {
+    use entities::EntityTag;
+
+    let mut game = Game::new();
+    game.set_time_scale(4.0);
+    let mut timeline = Timeline::parse("track a\n0 blink 1\n0.3 send - kill").unwrap();
+    for _ in 0..4 {
+        timeline.update(&mut game);
+        game.update();
+    }
+    assert!(timeline.finished());
+    assert!(game.positions(EntityTag::Untagged).is_empty());
+}
@@ -1,6 +1,5 @@
-use ggez::graphics;
-use ggez::graphics::{Font, Point2, Text};
-use ggez::Context;
+use engine::graphics::DrawPrimitives;
+use ggez::graphics::Point2;
 
 use entities::Renderable;
 
@@ -19,9 +18,7 @@ impl From<(Point2, Point2)> for DebugText {
 }
 
 impl Renderable for DebugText {
-    fn render(&mut self, ctx: &mut Context) {
-        let font: Font = Font::default_font().unwrap();
-        let t = Text::new(ctx, &self.text, &font).unwrap();
-        graphics::draw(ctx, &t, self.pos, 0.0);
+    fn render(&self, surface: &mut DrawPrimitives) {
+        surface.text(&self.text, self.pos);
     }
 }
@@ -1,27 +1,280 @@
-
 pub mod graphics {
     use nalgebra::*;
 
-    pub trait Color: Clone + Copy {
-        fn into_rgba(self) -> [f32; 4];
+    pub trait Color {
+        fn into_rgba(&self) -> [f32; 4];
+    }
+
+    impl Color for [f32; 4] {
+        fn into_rgba(&self) -> [f32; 4] {
+            *self
+        }
     }
 
+    #[derive(Clone, Copy, Debug, PartialEq)]
     pub enum DrawMode {
         Line(f32),
         Fill,
     }
 
+    /// 2D affine transform, maps `p` to `(a * x + c * y + tx, b * x + d * y + ty)`.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Transform {
+        pub a: f32,
+        pub b: f32,
+        pub c: f32,
+        pub d: f32,
+        pub tx: f32,
+        pub ty: f32,
+    }
+
+    // What an ellipse becomes under a transform: still an axis aligned ellipse
+    // backends can draw natively, or an outline when it got sheared or rotated.
+    pub enum MappedEllipse {
+        Ellipse(Point2<f32>, f32, f32),
+        Polygon(Vec<Point2<f32>>),
+    }
+
+    impl Transform {
+        pub fn identity() -> Self {
+            Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+        }
+        pub fn new(a: f32, b: f32, c: f32, d: f32, tx: f32, ty: f32) -> Self {
+            Self { a, b, c, d, tx, ty }
+        }
+        pub fn translate(x: f32, y: f32) -> Self {
+            Self::new(1.0, 0.0, 0.0, 1.0, x, y)
+        }
+        pub fn rotate(angle: f32) -> Self {
+            let (sin, cos) = angle.sin_cos();
+            Self::new(cos, sin, -sin, cos, 0.0, 0.0)
+        }
+        pub fn scale(x: f32, y: f32) -> Self {
+            Self::new(x, 0.0, 0.0, y, 0.0, 0.0)
+        }
+
+        // `other` is applied first, then `self`.
+        pub fn then(&self, other: &Transform) -> Self {
+            Self::new(
+                self.a * other.a + self.c * other.b,
+                self.b * other.a + self.d * other.b,
+                self.a * other.c + self.c * other.d,
+                self.b * other.c + self.d * other.d,
+                self.a * other.tx + self.c * other.ty + self.tx,
+                self.b * other.tx + self.d * other.ty + self.ty,
+            )
+        }
+
+        pub fn inverse(&self) -> Self {
+            let det = self.a * self.d - self.b * self.c;
+            let (a, b, c, d) = (self.d / det, -self.b / det, -self.c / det, self.a / det);
+            Self::new(
+                a,
+                b,
+                c,
+                d,
+                -(a * self.tx + c * self.ty),
+                -(b * self.tx + d * self.ty),
+            )
+        }
+
+        pub fn apply(&self, p: Point2<f32>) -> Point2<f32> {
+            Point2::new(
+                self.a * p.x + self.c * p.y + self.tx,
+                self.b * p.x + self.d * p.y + self.ty,
+            )
+        }
+
+        pub fn apply_all(&self, points: &[Point2<f32>]) -> Vec<Point2<f32>> {
+            points.iter().map(|p| self.apply(*p)).collect()
+        }
+
+        // Lengths like line widths scale with the average of both axes.
+        pub fn length(&self, length: f32) -> f32 {
+            length * (self.a * self.d - self.b * self.c).abs().sqrt()
+        }
+
+        pub fn mode(&self, mode: DrawMode) -> DrawMode {
+            match mode {
+                DrawMode::Line(width) => DrawMode::Line(self.length(width)),
+                DrawMode::Fill => DrawMode::Fill,
+            }
+        }
+
+        pub fn ellipse(&self, origin: Point2<f32>, width: f32, height: f32) -> MappedEllipse {
+            const EPSILON: f32 = 1e-6;
+            let center = self.apply(origin);
+            let x_axis = (self.a * width, self.b * width);
+            let y_axis = (self.c * height, self.d * height);
+            let length = |v: (f32, f32)| (v.0 * v.0 + v.1 * v.1).sqrt();
+            if x_axis.1.abs() < EPSILON && y_axis.0.abs() < EPSILON {
+                return MappedEllipse::Ellipse(center, x_axis.0.abs(), y_axis.1.abs());
+            }
+            if x_axis.0.abs() < EPSILON && y_axis.1.abs() < EPSILON {
+                return MappedEllipse::Ellipse(center, y_axis.0.abs(), x_axis.1.abs());
+            }
+            let orthogonal = (x_axis.0 * y_axis.0 + x_axis.1 * y_axis.1).abs() < EPSILON;
+            if orthogonal && (length(x_axis) - length(y_axis)).abs() < EPSILON {
+                return MappedEllipse::Ellipse(center, length(x_axis), length(y_axis));
+            }
+            let steps = ((width.max(height) * 2.0).ceil() as usize).max(8).min(128);
+            let points = (0..steps)
+                .map(|i| {
+                    let angle = i as f32 / steps as f32 * 2.0 * ::std::f32::consts::PI;
+                    self.apply(Point2::new(
+                        origin.x + width * angle.cos(),
+                        origin.y + height * angle.sin(),
+                    ))
+                })
+                .collect();
+            MappedEllipse::Polygon(points)
+        }
+    }
+
+    /// Transforms pushed on a surface, each one relative to the previous.
+    #[derive(Clone, Debug)]
+    pub struct TransformStack {
+        stack: Vec<Transform>,
+    }
+
+    impl TransformStack {
+        pub fn new() -> Self {
+            Self { stack: Vec::new() }
+        }
+        pub fn current(&self) -> Transform {
+            self.stack
+                .last()
+                .cloned()
+                .unwrap_or_else(Transform::identity)
+        }
+        pub fn push(&mut self, transform: Transform) {
+            let combined = self.current().then(&transform);
+            self.stack.push(combined);
+        }
+        pub fn pop(&mut self) {
+            self.stack
+                .pop()
+                .expect("pop_transform without a matching push_transform");
+        }
+    }
+
+    // Distance from `p` to the closest point of the segment from `a` to `b`.
+    pub fn segment_distance(p: Point2<f32>, a: Point2<f32>, b: Point2<f32>) -> f32 {
+        let ab = b - a;
+        let ap = p - a;
+        let length = ab.dot(&ab);
+        let t = match length > 0.0 {
+            true => (ap.dot(&ab) / length).max(0.0).min(1.0),
+            false => 0.0,
+        };
+        (ap - ab * t).norm()
+    }
+
+    // Even-odd rule, so concave and self-intersecting outlines work too.
+    pub fn polygon_contains(p: Point2<f32>, points: &[Point2<f32>]) -> bool {
+        // Fewer than three points enclose nothing.
+        if points.len() < 3 {
+            return false;
+        }
+        let mut inside = false;
+        let mut j = points.len() - 1;
+        for i in 0..points.len() {
+            let (a, b) = (points[i], points[j]);
+            if (a.y > p.y) != (b.y > p.y) && p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+                inside = !inside;
+            }
+            j = i;
+        }
+        inside
+    }
+
     pub trait Graphics {
-        fn clear<C: Color>(&mut self, C);
+        fn clear(&mut self, &Color);
         fn present(&mut self);
         // fn draw(Drawable, position: Point2, scale: f32);
     }
 
+    // Every primitive goes through the current transform of the surface. Text
+    // starts at the transformed origin, but it's never rotated or scaled.
     pub trait DrawPrimitives: Graphics {
-        fn set_color<C: Color>(&mut self, C);
+        fn transform_stack(&mut self) -> &mut TransformStack;
+
+        fn push_transform(&mut self, transform: Transform) {
+            self.transform_stack().push(transform);
+        }
+        fn pop_transform(&mut self) {
+            self.transform_stack().pop();
+        }
+        fn set_color(&mut self, &Color);
         fn circle(&mut self, DrawMode, origin: Point2<f32>, radius: f32);
         fn ellipse(&mut self, DrawMode, origin: Point2<f32>, width: f32, height: f32);
         fn line(&mut self, origin: Point2<f32>, target: Point2<f32>, width: f32);
+        fn text(&mut self, text: &str, origin: Point2<f32>);
+        // Convex or concave, the last point connects back to the first one.
+        fn polygon(&mut self, DrawMode, points: &[Point2<f32>]);
+
+        fn polyline(&mut self, points: &[Point2<f32>], width: f32) {
+            for segment in points.windows(2) {
+                self.line(segment[0], segment[1], width);
+            }
+            // round joins, so thick lines don't show gaps at the corners
+            if points.len() > 2 {
+                for &p in points[1..points.len() - 1].iter() {
+                    self.circle(DrawMode::Fill, p, width / 2.0);
+                }
+            }
+        }
+        fn rectangle(&mut self, mode: DrawMode, origin: Point2<f32>, width: f32, height: f32) {
+            self.polygon(
+                mode,
+                &[
+                    origin,
+                    Point2::new(origin.x + width, origin.y),
+                    Point2::new(origin.x + width, origin.y + height),
+                    Point2::new(origin.x, origin.y + height),
+                ],
+            );
+        }
+        // Angles in radians, clockwise on screen from the positive x axis.
+        fn arc(&mut self, origin: Point2<f32>, radius: f32, start: f32, end: f32, width: f32) {
+            self.polyline(&arc_points(origin, radius, start, end), width);
+        }
+        fn rounded_rectangle(
+            &mut self,
+            mode: DrawMode,
+            origin: Point2<f32>,
+            width: f32,
+            height: f32,
+            radius: f32,
+        ) {
+            use std::f32::consts::PI;
+            let r = radius.min(width / 2.0).min(height / 2.0).max(0.0);
+            let corners = [
+                (origin.x + width - r, origin.y + r, -PI / 2.0),
+                (origin.x + width - r, origin.y + height - r, 0.0),
+                (origin.x + r, origin.y + height - r, PI / 2.0),
+                (origin.x + r, origin.y + r, PI),
+            ];
+            let mut points = Vec::new();
+            for &(x, y, start) in corners.iter() {
+                points.extend(arc_points(Point2::new(x, y), r, start, start + PI / 2.0));
+            }
+            self.polygon(mode, &points);
+        }
+    }
+
+    // Points along an arc, close enough together to look smooth when joined.
+    pub fn arc_points(origin: Point2<f32>, radius: f32, start: f32, end: f32) -> Vec<Point2<f32>> {
+        let steps = ((end - start).abs() * radius / 4.0).ceil().max(1.0) as usize;
+        (0..steps + 1)
+            .map(|i| {
+                let angle = start + (end - start) * i as f32 / steps as f32;
+                Point2::new(
+                    origin.x + radius * angle.cos(),
+                    origin.y + radius * angle.sin(),
+                )
+            })
+            .collect()
     }
 
     pub trait Draw<S> {
@@ -33,5 +286,16 @@ pub mod graphics {
 pub mod logical {
     pub trait Update<C> {
         fn update(&mut self, &mut C);
+
+        // Asks the runner to stop.
+        fn finished(&self) -> bool {
+            false
+        }
+    }
+
+    // Keys as the backend reports them, e.g. ggez `Keycode`s or terminal keys.
+    pub trait Input<K> {
+        fn key_down(&mut self, K);
+        fn key_up(&mut self, K);
     }
 }
@@ -1,14 +1,13 @@
-use ggez::graphics;
-use ggez::graphics::Color;
-use ggez::graphics::{DrawMode, Rect};
-use ggez::Context;
+use engine::graphics::{DrawMode, DrawPrimitives};
+use ggez::graphics::Point2;
 
 use entities::{Entity, EntityData};
 use messages::{Message, MessageSender};
 use palette::Palette;
+use playfield;
 
-use W_HEIGHT;
-use W_WIDTH;
+// Blink times are counted this much faster than real time.
+const FADE_SPEED: f32 = 3.0;
 
 pub struct Blink {
     entity_data: EntityData,
@@ -39,20 +38,24 @@ impl Entity for Blink {
     fn entity_data(&self) -> &EntityData {
         &self.entity_data
     }
-    fn update(&mut self, _ctx: &mut Context) {
-        self.remaining_time -= 0.1;
+    fn update(&mut self, delta_time: f32) {
+        self.remaining_time -= delta_time * FADE_SPEED;
         if self.remaining_time < 0.0 {
             self.die();
         }
     }
-    fn render(&mut self, ctx: &mut Context) {
+    fn render(&self, surface: &mut DrawPrimitives) {
         let opacity = self.remaining_time / self.total_time;
-        graphics::set_color(ctx, Color::from(Palette::Blink(opacity))).unwrap();
-        graphics::rectangle(
-            ctx,
+        surface.set_color(&Palette::Blink(opacity));
+        surface.rectangle(
             DrawMode::Fill,
-            Rect::new(0.0, 0.0, W_WIDTH as f32, W_HEIGHT as f32),
-        ).unwrap();
+            Point2::new(0.0, 0.0),
+            playfield::width(),
+            playfield::height(),
+        );
     }
     fn receive_message(&mut self, _sender: MessageSender, _message: Message) {}
+    fn screen_space(&self) -> bool {
+        true
+    }
 }
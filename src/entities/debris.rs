@@ -0,0 +1,76 @@
+use engine::graphics::DrawPrimitives;
+use ggez::graphics::Point2;
+
+use entities::enemy::BodyData;
+use entities::{Entity, EntityData};
+use math::VectorUtils;
+use mekano::Mekano;
+use mekano_renderer::Render;
+use messages::{Message, MessageSender};
+
+const LIFETIME: f32 = 4.0;
+// Fraction of the velocity kept every FRAME seconds.
+const DRAG: f32 = 0.98;
+const FRAME: f32 = 0.033;
+
+#[derive(Debug)]
+pub struct Debris {
+    entity_data: EntityData,
+    mekano: Mekano<BodyData>,
+    // In pixels and radians per second.
+    velocity: Point2,
+    spin: f32,
+    remaining_time: f32,
+}
+
+impl Debris {
+    pub fn new(mekano: Mekano<BodyData>, velocity: Point2, spin: f32) -> Self {
+        Self {
+            entity_data: EntityData::new_at(mekano.data().origin),
+            mekano,
+            velocity,
+            spin,
+            remaining_time: LIFETIME,
+        }
+    }
+}
+
+impl Entity for Debris {
+    fn entity_data_mut(&mut self) -> &mut EntityData {
+        &mut self.entity_data
+    }
+    fn entity_data(&self) -> &EntityData {
+        &self.entity_data
+    }
+    fn update(&mut self, delta_time: f32) {
+        self.remaining_time -= delta_time;
+        if self.remaining_time < 0.0 {
+            self.die();
+        }
+        let pos = self.entity_data.pos.add(self.velocity.mul(delta_time));
+        self.entity_data.pos = pos;
+        self.velocity = self.velocity.mul(DRAG.powf(delta_time / FRAME));
+
+        let root = self.mekano.data_mut();
+        root.origin = pos;
+        root.angle += self.spin * delta_time;
+    }
+    fn render(&self, surface: &mut DrawPrimitives) {
+        self.mekano.render(surface);
+    }
+    fn receive_message(&mut self, _sender: MessageSender, _message: Message) {}
+}
+
+#[test]
+fn debris_moves_by_time_not_frames() {
+    use mekano_renderer::Shape;
+
+    let part = || Mekano::End(BodyData::new(Shape::Circle(2.0)));
+    let mut slow = Debris::new(part(), Point2::new(90.0, 0.0), 6.0);
+    let mut fast = Debris::new(part(), Point2::new(90.0, 0.0), 6.0);
+    slow.update(0.066);
+    fast.update(0.033);
+    fast.update(0.033);
+    assert!((slow.get_pos().x - fast.get_pos().x).abs() < 0.2);
+    assert!((slow.mekano.data().angle - fast.mekano.data().angle).abs() < 0.001);
+}
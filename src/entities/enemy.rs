@@ -1,19 +1,36 @@
+use engine::graphics::DrawPrimitives;
 use ggez::graphics::Point2;
-use ggez::timer::get_delta;
-use ggez::Context;
 
 use debug::DebugText;
+use entities::debris::Debris;
+use entities::particles::{Emitter, EmitterConfig};
 use entities::{Entity, EntityData, EntityTag, Renderable};
-use math::VectorUtils;
+use math::{fork, Randomize, VectorUtils};
 use messages::{Message, MessageSender};
 use std::time::Duration;
 
 use mekano::Mekano;
+use mekano_collider;
 
+use mekano_format::{
+    check_keys, field, format_f32, format_pair, format_point, format_shape, format_triple,
+    parse_f32, parse_pair, parse_point, parse_shape, parse_triple, Fields,
+};
+use mekano_ik;
+use mekano_ik::Joint;
 use mekano_renderer;
-use mekano_renderer::Render;
+use mekano_renderer::{world_transforms, Render, Shape};
+use rand::{SeedableRng, StdRng};
+use std::f32::consts::PI;
 
-const PRECISION: f32 = 0.5;
+// In pixels and radians per second.
+const DEBRIS_SPEED: f32 = 90.0;
+const DEBRIS_SPIN: f32 = 6.0;
+const BREAK_SPARKS: usize = 40;
+const IK_ITERATIONS: usize = 4;
+// Screen shake for every hit, and for every part breaking off.
+const HIT_TRAUMA: f32 = 0.1;
+const BREAK_TRAUMA: f32 = 0.4;
 
 pub trait EnemyPath {
     fn get(&self, t: f32) -> Point2;
@@ -31,17 +48,132 @@ where
     cycle: f32,
     animation_speed: f32,
     mekano: Mekano<BodyData>,
+    debris: Vec<Debris>,
+    sparks: Vec<Emitter>,
+    target: Option<Point2>,
+    trauma: f32,
+    // Where the spin of debris comes from.
+    rng: StdRng,
 }
 
-#[derive(Debug)]
-struct BodyData(Point2, f32);
+#[derive(Debug, Clone, PartialEq)]
+pub struct BodyData {
+    pub origin: Point2,
+    pub length: f32,
+    pub angle: f32,
+    pub shape: Shape,
+    pub animation: Animation,
+    pub health: f32,
+    pub limits: (f32, f32),
+    sway: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Animation {
+    pub speed: f32,
+    pub amplitude: f32,
+    pub phase: f32,
+}
+
+impl BodyData {
+    pub fn new(shape: Shape) -> Self {
+        Self {
+            origin: Point2::zero(),
+            length: 0.0,
+            angle: 0.0,
+            shape,
+            animation: Animation::still(),
+            health: 10.0,
+            limits: (-PI, PI),
+            sway: 0.0,
+        }
+    }
+    fn animate(&mut self, cycle: f32) {
+        let a = self.animation;
+        self.sway = (cycle * a.speed + a.phase).sin() * a.amplitude;
+    }
+}
+
+impl Animation {
+    pub fn still() -> Self {
+        Self {
+            speed: 0.0,
+            amplitude: 0.0,
+            phase: 0.0,
+        }
+    }
+}
 
 impl mekano_renderer::Data for BodyData {
-    fn shape(&self) -> mekano_renderer::Shape {
-        mekano_renderer::Shape::Circle(self.1)
+    fn shape(&self) -> Shape {
+        self.shape
     }
     fn origin(&self) -> Point2 {
-        self.0
+        self.origin
+    }
+    fn rotation(&self) -> f32 {
+        self.angle + self.sway
+    }
+    fn length(&self) -> f32 {
+        self.length
+    }
+}
+
+impl Joint for BodyData {
+    fn angle(&self) -> f32 {
+        self.angle
+    }
+    fn set_angle(&mut self, angle: f32) {
+        self.angle = angle;
+    }
+    fn limits(&self) -> (f32, f32) {
+        self.limits
+    }
+}
+
+impl Fields for BodyData {
+    fn to_fields(&self) -> Vec<(&'static str, String)> {
+        let a = self.animation;
+        vec![
+            ("shape", format_shape(self.shape)),
+            ("origin", format_point(self.origin)),
+            ("length", format_f32(self.length)),
+            ("angle", format_f32(self.angle)),
+            ("animation", format_triple((a.speed, a.amplitude, a.phase))),
+            ("health", format_f32(self.health)),
+            ("limits", format_pair(self.limits)),
+        ]
+    }
+    fn from_fields(fields: &[(String, String)]) -> Result<Self, String> {
+        let known = [
+            "shape",
+            "origin",
+            "length",
+            "angle",
+            "animation",
+            "health",
+            "limits",
+        ];
+        check_keys(fields, &known)?;
+        let (speed, amplitude, phase) =
+            parse_triple(field(fields, "animation").unwrap_or("(0,0,0)"))?;
+        Ok(Self {
+            origin: parse_point(field(fields, "origin").unwrap_or("(0,0)"))?,
+            length: parse_f32(field(fields, "length").unwrap_or("0"))?,
+            angle: parse_f32(field(fields, "angle").unwrap_or("0"))?,
+            shape: parse_shape(field(fields, "shape").unwrap_or("none"))?,
+            animation: Animation {
+                speed,
+                amplitude,
+                phase,
+            },
+            health: parse_f32(field(fields, "health").unwrap_or("10"))?,
+            limits: match field(fields, "limits") {
+                Some(limits) => parse_pair(limits)?,
+                None => (-PI, PI),
+            },
+            sway: 0.0,
+        })
     }
 }
 
@@ -51,6 +183,12 @@ where
 {
     pub fn new(path: P, duration: Duration) -> Self {
         let pos = path.get(0.0);
+        Self::with_mekano(path, duration, Self::generate_mekano_model(pos))
+    }
+
+    pub fn with_mekano(path: P, duration: Duration, mut mekano: Mekano<BodyData>) -> Self {
+        let pos = path.get(0.0);
+        mekano.data_mut().origin = pos;
         Self {
             path,
             duration,
@@ -58,12 +196,95 @@ where
             entity_data: EntityData::new_at(pos),
             cycle: 0.0,
             animation_speed: 1.0,
-            mekano: Self::generate_mekano_model(pos),
+            mekano,
+            debris: Vec::new(),
+            sparks: Vec::new(),
+            target: None,
+            trauma: 0.0,
+            rng: StdRng::from_seed(&[0]),
         }
     }
 
-    fn generate_mekano_model(origin: Point2) -> Mekano<BodyData> {
-        Mekano::End(BodyData(origin, 20.0))
+    // Draws from a generator seeded by `rng` instead of a fixed one.
+    pub fn seeded(mut self, rng: &mut StdRng) -> Self {
+        self.rng = fork(rng);
+        self
+    }
+
+    pub fn damage(&mut self, node: usize, amount: f32) {
+        let broken = match self.mekano.get_mut(node) {
+            Some(n) => {
+                let data = n.data_mut();
+                data.health -= amount;
+                data.health <= 0.0
+            }
+            None => false,
+        };
+        self.trauma += HIT_TRAUMA;
+        if !broken {
+            return;
+        }
+        let (origin, rotation) = world_transforms(&self.mekano)[node];
+        self.trauma += BREAK_TRAUMA;
+        self.sparks.push(
+            Emitter::new(origin, EmitterConfig::sparks())
+                .burst(BREAK_SPARKS)
+                .lasting(0.0)
+                .seeded(&mut self.rng),
+        );
+        if node == 0 {
+            let body = self.mekano.clone();
+            self.break_off(body, origin, rotation);
+            self.die();
+        } else if let Some(part) = self.mekano.detach(node) {
+            self.break_off(part, origin, rotation);
+        }
+    }
+
+    fn break_off(&mut self, mut part: Mekano<BodyData>, origin: Point2, rotation: f32) {
+        {
+            let data = part.data_mut();
+            data.origin = origin;
+            data.angle = rotation - data.sway;
+        }
+        let away = origin.sub(self.entity_data.pos);
+        let direction = match away.norm() > 0.001 {
+            true => away.unit(),
+            false => Point2::up(),
+        };
+        let spin = 0.0f32.rand(&mut self.rng, DEBRIS_SPIN);
+        self.debris
+            .push(Debris::new(part, direction.mul(DEBRIS_SPEED), spin));
+    }
+
+    pub fn generate_mekano_model(origin: Point2) -> Mekano<BodyData> {
+        let link = |length: f32, radius: f32| BodyData {
+            length,
+            limits: (-0.8, 0.8),
+            ..BodyData::new(Shape::Circle(radius))
+        };
+        let tentacle = |angle: f32| {
+            let head = BodyData {
+                angle,
+                limits: (0.2, PI - 0.2),
+                ..link(22.0, 7.0)
+            };
+            Mekano::Segment(
+                head,
+                Box::new(Mekano::Segment(
+                    link(14.0, 5.0),
+                    Box::new(Mekano::End(link(11.0, 3.0))),
+                )),
+            )
+        };
+        Mekano::Split(
+            BodyData {
+                origin,
+                ..BodyData::new(Shape::Circle(20.0))
+            },
+            Box::new(tentacle(1.2)),
+            Box::new(tentacle(PI - 1.2)),
+        )
     }
 }
 
@@ -83,27 +304,54 @@ where
     fn entity_data(&self) -> &EntityData {
         &self.entity_data
     }
-    fn update(&mut self, ctx: &mut Context) {
-        let delta = get_delta(ctx);
-        self.current_duration += delta;
+    fn update(&mut self, delta_time: f32) {
+        self.current_duration += Duration::from_millis((delta_time * 1000.0) as u64);
         if self.current_duration > self.duration {
             self.entity_data.alive = false;
         }
         let path_position = duration_ratio(self.current_duration, self.duration);
         self.entity_data.pos = self.path.get(path_position);
-        self.mekano.data_mut().0 = self.entity_data.pos;
+        self.mekano.data_mut().origin = self.entity_data.pos;
+
+        self.cycle += delta_time * self.animation_speed;
+        let cycle = self.cycle;
+        self.mekano
+            .for_each_mut(&mut |d: &mut BodyData| d.animate(cycle));
+
+        if let Some(target) = self.target {
+            for head in mekano_ik::chains(&self.mekano) {
+                mekano_ik::reach(&mut self.mekano, head, target, IK_ITERATIONS);
+            }
+        }
     }
-    fn render(&mut self, ctx: &mut Context) {
-        self.mekano.render(ctx);
-        self.path.render(ctx);
+    fn render(&self, surface: &mut DrawPrimitives) {
+        self.mekano.render(surface);
+        self.path.render(surface);
         let mut dt: DebugText = (
             self.entity_data.pos,
             self.entity_data.pos.add(Point2::up().mul(40.0)),
         ).into();
-        dt.render(ctx);
+        dt.render(surface);
+    }
+    fn receive_message(&mut self, _sender: MessageSender, message: Message) {
+        match message {
+            Message::Damage(node, amount) => self.damage(node, amount),
+            Message::Target(target) => self.target = Some(target),
+            _ => (),
+        }
     }
-    fn receive_message(&mut self, _sender: MessageSender, _message: Message) {}
     fn get_tag(&self) -> EntityTag {
         EntityTag::Enemy
     }
+    fn hit_test(&self, point: Point2, radius: f32) -> Option<usize> {
+        mekano_collider::hit(&self.mekano, point, radius)
+    }
+    fn take_spawned(&mut self) -> Vec<Box<Entity>> {
+        let debris = self.debris.drain(..).map(|d| Box::new(d) as Box<Entity>);
+        let sparks = self.sparks.drain(..).map(|s| Box::new(s) as Box<Entity>);
+        debris.chain(sparks).collect()
+    }
+    fn take_trauma(&mut self) -> f32 {
+        ::std::mem::replace(&mut self.trauma, 0.0)
+    }
 }
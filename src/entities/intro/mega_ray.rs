@@ -1,15 +1,10 @@
-use ggez::graphics;
-use ggez::graphics::Color;
-use ggez::graphics::{DrawMode, Point2};
-use ggez::Context;
+use engine::graphics::{DrawMode, DrawPrimitives};
+use ggez::graphics::Point2;
 
 use entities::{Entity, EntityData};
 use messages::{Message, MessageSender};
 use palette::Palette;
-
-use math::VectorUtils;
-
-const PRECISION: f32 = 0.5;
+use playfield;
 
 pub struct MegaRay {
     entity_data: EntityData,
@@ -36,54 +31,28 @@ impl Entity for MegaRay {
     fn entity_data(&self) -> &EntityData {
         &self.entity_data
     }
-    fn update(&mut self, _ctx: &mut Context) {
+    fn update(&mut self, _delta_time: f32) {
         self.cycle += 0.1;
     }
-    fn render(&mut self, ctx: &mut Context) {
+    fn render(&self, surface: &mut DrawPrimitives) {
         let cycle = self.cycle;
+        let pos = self.entity_data.pos;
+        let bottom = Point2::new(pos.x, playfield::height());
 
         if cycle % 0.2 > 0.1 {
-            graphics::set_color(ctx, Color::from(Palette::Light(0.4))).unwrap();
-            graphics::circle(
-                ctx,
-                DrawMode::Fill,
-                self.entity_data.pos,
-                60.0 + (cycle * 23.0).sin() * 60.0,
-                PRECISION,
-            ).unwrap();
-            graphics::line(
-                ctx,
-                &[
-                    self.entity_data.pos,
-                    self.entity_data.pos.add(Point2::new(0.0, 400.0)),
-                ],
-                40.0,
-            ).unwrap();
-            graphics::circle(ctx, DrawMode::Fill, self.entity_data.pos, 40.0, PRECISION).unwrap();
+            surface.set_color(&Palette::Light(0.4));
+            surface.circle(DrawMode::Fill, pos, 60.0 + (cycle * 23.0).sin() * 60.0);
+            surface.line(pos, bottom, 40.0);
+            surface.circle(DrawMode::Fill, pos, 40.0);
         }
         if cycle % 0.3 > 0.2 {
-            graphics::circle(
-                ctx,
-                DrawMode::Fill,
-                self.entity_data.pos,
-                60.0 + (cycle * 0.6).sin() * 20.0,
-                PRECISION,
-            ).unwrap();
-            graphics::line(
-                ctx,
-                &[
-                    self.entity_data.pos,
-                    self.entity_data.pos.add(Point2::new(0.0, 400.0)),
-                ],
-                35.0 + (cycle * 9.0).sin() * 30.0,
-            ).unwrap();
-            graphics::circle(
-                ctx,
-                DrawMode::Fill,
-                self.entity_data.pos,
+            surface.circle(DrawMode::Fill, pos, 60.0 + (cycle * 0.6).sin() * 20.0);
+            surface.line(
+                pos,
+                bottom,
                 35.0 + (cycle * 9.0).sin() * 30.0,
-                PRECISION,
-            ).unwrap();
+            );
+            surface.circle(DrawMode::Fill, pos, 35.0 + (cycle * 9.0).sin() * 30.0);
         }
     }
     fn receive_message(&mut self, _sender: MessageSender, message: Message) {
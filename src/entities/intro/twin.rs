@@ -1,21 +1,20 @@
-use ggez::graphics;
-use ggez::graphics::Color;
-use ggez::graphics::{DrawMode, Point2};
-use ggez::Context;
+use engine::graphics::{DrawMode, DrawPrimitives};
+use ggez::graphics::Point2;
 
 use entities::{Entity, EntityData};
 use messages::{Direction, Message, MessageSender};
 use palette::Palette;
 
 use math::VectorUtils;
+use tween::{Ease, Family, Tween};
 
-const PRECISION: f32 = 0.5;
+// Pixels per second walked when moved sideways.
+const WALK_SPEED: f32 = 60.0;
 
 pub struct TwinIntro {
     entity_data: EntityData,
     cycle: f32,
-    going_to: Option<Point2>,
-    speed: f32,
+    walk: Option<Tween<Point2>>,
 }
 
 impl Entity for TwinIntro {
@@ -25,31 +24,33 @@ impl Entity for TwinIntro {
     fn entity_data(&self) -> &EntityData {
         &self.entity_data
     }
-    fn update(&mut self, _ctx: &mut Context) {
+    fn update(&mut self, delta_time: f32) {
         self.cycle += 0.1;
-        if let Some(vector) = self.going_to {
-            let delta = vector.unit().mul(self.speed);
-            let pos = self.get_pos();
-            self.set_pos(pos.add(delta));
-
-            self.going_to = match vector.norm() > delta.norm() {
-                true => Some(vector.sub(delta)),
-                false => None,
+        if let Some(mut walk) = self.walk.take() {
+            self.set_pos(walk.update(delta_time));
+            if !walk.finished() {
+                self.walk = Some(walk);
             }
         }
     }
-    fn render(&mut self, ctx: &mut Context) {
-        graphics::set_color(ctx, Color::from(Palette::Player)).unwrap();
-        graphics::circle(ctx, DrawMode::Fill, self.entity_data.pos, 20.0, PRECISION).unwrap();
+    fn render(&self, surface: &mut DrawPrimitives) {
+        surface.set_color(&Palette::Player);
+        surface.circle(DrawMode::Fill, self.entity_data.pos, 20.0);
     }
     fn receive_message(&mut self, _sender: MessageSender, message: Message) {
         match message {
             Message::Move(direction, distance) => {
-                self.going_to = match direction {
-                    Direction::Right => Some(Point2::new(distance, 0.0)),
-                    Direction::Left => Some(Point2::new(-distance, 0.0)),
-                    _ => None,
+                let step = match direction {
+                    Direction::Right => Point2::right(),
+                    Direction::Left => Point2::left(),
+                    _ => return,
                 };
+                let pos = self.get_pos();
+                self.walk = Some(Tween::new(pos).to(
+                    pos.add(step.mul(distance)),
+                    distance / WALK_SPEED,
+                    Ease::InOut(Family::Quad),
+                ));
             }
             Message::Kill => {
                 self.die();
@@ -67,8 +68,7 @@ impl TwinIntro {
                 ..EntityData::new()
             },
             cycle: 0.0,
-            going_to: None,
-            speed: 2.0,
+            walk: None,
         }
     }
 }
@@ -1,5 +1,5 @@
+use engine::graphics::DrawPrimitives;
 use ggez::graphics::Point2;
-use ggez::Context;
 use messages::{Message, MessageSender};
 
 pub type EntityId = i32;
@@ -7,6 +7,8 @@ pub type EntityId = i32;
 #[derive(PartialEq, Copy, Clone, Debug)]
 pub enum EntityTag {
     Player(EntityTagPlayer),
+    // Fired by that player.
+    Shot(EntityTagPlayer),
     Stars,
     Enemy,
     Untagged,
@@ -58,8 +60,8 @@ impl EntityData {
 pub trait Entity {
     fn entity_data_mut(&mut self) -> &mut EntityData;
     fn entity_data(&self) -> &EntityData;
-    fn update(&mut self, ctx: &mut Context);
-    fn render(&mut self, ctx: &mut Context);
+    fn update(&mut self, delta_time: f32);
+    fn render(&self, surface: &mut DrawPrimitives);
     fn receive_message(&mut self, sender: MessageSender, message: Message);
     fn is_alive(&self) -> bool {
         self.entity_data().alive
@@ -79,23 +81,45 @@ pub trait Entity {
     fn get_tag(&self) -> EntityTag {
         EntityTag::Untagged
     }
+    fn take_spawned(&mut self) -> Vec<Box<Entity>> {
+        Vec::new()
+    }
+    fn hit_test(&self, _point: Point2, _radius: f32) -> Option<usize> {
+        None
+    }
+    // Screen shake caused since the last call, see `Camera::add_trauma`.
+    fn take_trauma(&mut self) -> f32 {
+        0.0
+    }
+    // Drawn in window coordinates, ignoring the camera.
+    fn screen_space(&self) -> bool {
+        false
+    }
+    // Moved to the given entity's position after every update. Gets
+    // `Message::Stop` once that entity is gone.
+    fn attached_to(&self) -> Option<EntityId> {
+        None
+    }
 }
 
 pub trait Renderable {
-    fn render(&mut self, ctx: &mut Context);
+    fn render(&self, surface: &mut DrawPrimitives);
 }
 
 impl<T> Renderable for T
 where
     T: Entity,
 {
-    fn render(&mut self, ctx: &mut Context) {
-        self.render(ctx);
+    fn render(&self, surface: &mut DrawPrimitives) {
+        self.render(surface);
     }
 }
 
 pub mod blink;
+pub mod debris;
 pub mod enemy;
 pub mod intro;
+pub mod particles;
+pub mod shot;
 pub mod stars;
 pub mod twin;
@@ -0,0 +1,321 @@
+use engine::graphics::{Color, DrawMode, DrawPrimitives};
+use ggez::graphics::Point2;
+
+use color::{Gradient, Rgba};
+use entities::{Entity, EntityData, EntityId};
+use math::{fork, Randomize, VectorUtils};
+use messages::{Message, MessageSender};
+use palette::Palette;
+use rand::{SeedableRng, StdRng};
+use std::f32::consts::{FRAC_PI_2, PI};
+use tween::Keyframes;
+
+// Colors over life are looked up instead of sampled for every particle.
+const COLOR_STEPS: usize = 32;
+// Seconds of movement a line particle trails behind it.
+const STREAK: f32 = 0.04;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParticleShape {
+    Circle,
+    Line,
+}
+
+/// Values placed along [0, 1] with straight lines in between.
+#[derive(Clone, Debug)]
+pub struct Curve {
+    points: Keyframes<f32>,
+}
+
+impl Curve {
+    pub fn new(points: Vec<(f32, f32)>) -> Self {
+        Curve {
+            points: Keyframes::new(points),
+        }
+    }
+
+    pub fn constant(value: f32) -> Self {
+        Curve::new(vec![(0.0, value)])
+    }
+
+    pub fn sample(&self, t: f32) -> f32 {
+        self.points.sample(t)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EmitterConfig {
+    // Particles per second while the emitter runs.
+    pub rate: f32,
+    pub lifetime: f32,
+    pub lifetime_deviation: f32,
+    // Angle particles leave at, deviating by `spread` radians.
+    pub direction: f32,
+    pub spread: f32,
+    // In pixels per second.
+    pub speed: f32,
+    pub speed_deviation: f32,
+    // Fraction of the velocity lost every second.
+    pub drag: f32,
+    pub gravity: Point2,
+    // Radius or line width, over the particle's life.
+    pub size: Curve,
+    pub color: Gradient,
+    pub shape: ParticleShape,
+    // The most particles alive at once, the pool is allocated up front.
+    pub capacity: usize,
+}
+
+impl EmitterConfig {
+    pub fn sparks() -> Self {
+        let light = Palette::Light(1.0).into_rgba();
+        let mut faded = light;
+        faded[3] = 0.0;
+        EmitterConfig {
+            rate: 0.0,
+            lifetime: 0.6,
+            lifetime_deviation: 0.2,
+            direction: 0.0,
+            spread: PI,
+            speed: 180.0,
+            speed_deviation: 60.0,
+            drag: 0.9,
+            gravity: Point2::new(0.0, 60.0),
+            size: Curve::new(vec![(0.0, 2.0), (1.0, 0.5)]),
+            color: Gradient::even(&[[1.0, 1.0, 1.0, 1.0], light, faded]),
+            shape: ParticleShape::Line,
+            capacity: 256,
+        }
+    }
+
+    pub fn thruster() -> Self {
+        let player = Palette::Player.into_rgba();
+        let mut faded = player;
+        faded[3] = 0.0;
+        EmitterConfig {
+            rate: 60.0,
+            lifetime: 0.35,
+            lifetime_deviation: 0.05,
+            direction: FRAC_PI_2,
+            spread: 0.15,
+            speed: 150.0,
+            speed_deviation: 20.0,
+            drag: 0.5,
+            gravity: Point2::zero(),
+            size: Curve::new(vec![(0.0, 3.0), (1.0, 0.0)]),
+            color: Gradient::even(&[player, faded]),
+            shape: ParticleShape::Circle,
+            capacity: 64,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Particle {
+    pos: Point2,
+    velocity: Point2,
+    age: f32,
+    lifetime: f32,
+}
+
+/// Spawns particles at its position, which follows another entity when
+/// attached. Dies once stopped and every particle faded out.
+#[derive(Debug)]
+pub struct Emitter {
+    entity_data: EntityData,
+    config: EmitterConfig,
+    colors: Vec<Rgba>,
+    particles: Vec<Particle>,
+    running: bool,
+    remaining_time: Option<f32>,
+    pending: f32,
+    burst: usize,
+    attached: Option<EntityId>,
+    offset: Point2,
+    // Where the spread of each particle comes from.
+    rng: StdRng,
+}
+
+impl Emitter {
+    pub fn new(pos: Point2, config: EmitterConfig) -> Self {
+        let colors = (0..COLOR_STEPS)
+            .map(|i| config.color.sample(i as f32 / (COLOR_STEPS - 1) as f32))
+            .collect();
+        Self {
+            entity_data: EntityData {
+                z_order: 5.0,
+                ..EntityData::new_at(pos)
+            },
+            particles: Vec::with_capacity(config.capacity),
+            config,
+            colors,
+            running: true,
+            remaining_time: None,
+            pending: 0.0,
+            burst: 0,
+            attached: None,
+            offset: Point2::zero(),
+            rng: StdRng::from_seed(&[0]),
+        }
+    }
+
+    // Draws the spread from a generator forked off `rng`.
+    pub fn seeded(mut self, rng: &mut StdRng) -> Self {
+        self.rng = fork(rng);
+        self
+    }
+
+    // Spawns `count` particles on the next update, on top of the rate.
+    pub fn burst(mut self, count: usize) -> Self {
+        self.burst += count;
+        self
+    }
+
+    // Stops spawning after `seconds`.
+    pub fn lasting(mut self, seconds: f32) -> Self {
+        self.remaining_time = Some(seconds);
+        self
+    }
+
+    // Follows `target` around, spawning `offset` away from it.
+    pub fn attach(mut self, target: EntityId, offset: Point2) -> Self {
+        self.attached = Some(target);
+        self.offset = offset;
+        self
+    }
+
+    pub fn count(&self) -> usize {
+        self.particles.len()
+    }
+
+    fn spawn(&mut self, count: usize) {
+        let origin = self.entity_data.pos.add(self.offset);
+        for _ in 0..count {
+            if self.particles.len() == self.config.capacity {
+                return;
+            }
+            let angle = self
+                .config
+                .direction
+                .gauss(&mut self.rng, self.config.spread);
+            let speed = self
+                .config
+                .speed
+                .gauss(&mut self.rng, self.config.speed_deviation);
+            let lifetime = self
+                .config
+                .lifetime
+                .gauss(&mut self.rng, self.config.lifetime_deviation);
+            self.particles.push(Particle {
+                pos: origin,
+                velocity: Point2::right().rotate(angle).mul(speed),
+                age: 0.0,
+                lifetime: lifetime.max(0.01),
+            });
+        }
+    }
+}
+
+impl Entity for Emitter {
+    fn entity_data_mut(&mut self) -> &mut EntityData {
+        &mut self.entity_data
+    }
+    fn entity_data(&self) -> &EntityData {
+        &self.entity_data
+    }
+    fn update(&mut self, delta_time: f32) {
+        let mut count = ::std::mem::replace(&mut self.burst, 0);
+        if self.running {
+            self.pending += self.config.rate * delta_time;
+            count += self.pending as usize;
+            self.pending %= 1.0;
+        }
+        self.spawn(count);
+        if let Some(remaining) = self.remaining_time {
+            self.remaining_time = Some(remaining - delta_time);
+            if remaining - delta_time <= 0.0 {
+                self.running = false;
+            }
+        }
+
+        let drag = (1.0 - self.config.drag).max(0.0).powf(delta_time);
+        let gravity = self.config.gravity.mul(delta_time);
+        for p in self.particles.iter_mut() {
+            p.age += delta_time;
+            p.velocity = p.velocity.add(gravity).mul(drag);
+            p.pos = p.pos.add(p.velocity.mul(delta_time));
+        }
+        self.particles.retain(|p| p.age < p.lifetime);
+
+        if !self.running && self.particles.is_empty() {
+            self.die();
+        }
+    }
+    fn render(&self, surface: &mut DrawPrimitives) {
+        let last = (COLOR_STEPS - 1) as f32;
+        for p in self.particles.iter() {
+            let life = p.age / p.lifetime;
+            let size = self.config.size.sample(life);
+            surface.set_color(&self.colors[(life * last) as usize]);
+            match self.config.shape {
+                ParticleShape::Circle => surface.circle(DrawMode::Fill, p.pos, size),
+                ParticleShape::Line => {
+                    let tail = p.pos.sub(p.velocity.mul(STREAK));
+                    surface.line(tail, p.pos, size)
+                }
+            }
+        }
+    }
+    fn receive_message(&mut self, _sender: MessageSender, message: Message) {
+        match message {
+            Message::Start => self.running = true,
+            Message::Stop => {
+                self.running = false;
+                self.attached = None;
+            }
+            _ => (),
+        }
+    }
+    fn attached_to(&self) -> Option<EntityId> {
+        self.attached
+    }
+}
+
+#[test]
+fn emitter_pools_particles() {
+    let mut config = EmitterConfig::sparks();
+    config.capacity = 10;
+    let mut emitter = Emitter::new(Point2::zero(), config).burst(50).lasting(0.1);
+    emitter.update(0.05);
+    assert_eq!(emitter.count(), 10);
+    for _ in 0..40 {
+        emitter.update(0.05);
+    }
+    assert_eq!(emitter.count(), 0);
+    assert!(!emitter.is_alive());
+}
+
+#[test]
+fn curves_sort_points_and_step() {
+    assert_eq!(Curve::new(vec![(1.0, 0.0), (0.0, 2.0)]).sample(0.25), 1.5);
+    let step = Curve::new(vec![(0.0, 1.0), (0.5, 1.0), (0.5, 3.0), (1.0, 3.0)]);
+    assert_eq!(step.sample(0.4), 1.0);
+    assert_eq!(step.sample(0.6), 3.0);
+}
+
+#[test]
+fn seeded_emitters_repeat() {
+    let spawn = || {
+        let mut rng = StdRng::from_seed(&[2]);
+        let mut emitter = Emitter::new(Point2::zero(), EmitterConfig::sparks())
+            .burst(5)
+            .seeded(&mut rng);
+        emitter.update(0.05);
+        emitter
+            .particles
+            .iter()
+            .map(|p| p.velocity)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(spawn(), spawn());
+}
@@ -0,0 +1,63 @@
+use engine::graphics::DrawPrimitives;
+use ggez::graphics::Point2;
+
+use entities::{Entity, EntityData, EntityTag, EntityTagPlayer};
+use math::VectorUtils;
+use messages::{Message, MessageSender};
+use palette::Palette;
+
+// Seconds of flight a shot trails behind it.
+const STREAK: f32 = 0.03;
+const WIDTH: f32 = 3.0;
+
+/// Flies straight until it runs out of time. Hitting enemies is left to the
+/// play state, which knows who fired it from the tag.
+pub struct Shot {
+    entity_data: EntityData,
+    velocity: Point2,
+    remaining_time: f32,
+    player: EntityTagPlayer,
+}
+
+impl Shot {
+    pub fn new(pos: Point2, velocity: Point2, lifetime: f32, player: EntityTagPlayer) -> Self {
+        Self {
+            entity_data: EntityData {
+                z_order: 4.0,
+                ..EntityData::new_at(pos)
+            },
+            velocity,
+            remaining_time: lifetime,
+            player,
+        }
+    }
+}
+
+impl Entity for Shot {
+    fn entity_data_mut(&mut self) -> &mut EntityData {
+        &mut self.entity_data
+    }
+    fn entity_data(&self) -> &EntityData {
+        &self.entity_data
+    }
+    fn update(&mut self, delta_time: f32) {
+        self.entity_data.pos = self.entity_data.pos.add(self.velocity.mul(delta_time));
+        self.remaining_time -= delta_time;
+        if self.remaining_time <= 0.0 {
+            self.die();
+        }
+    }
+    fn render(&self, surface: &mut DrawPrimitives) {
+        let pos = self.entity_data.pos;
+        surface.set_color(&Palette::Player);
+        surface.line(pos.sub(self.velocity.mul(STREAK)), pos, WIDTH);
+    }
+    fn receive_message(&mut self, _sender: MessageSender, message: Message) {
+        if let Message::Kill = message {
+            self.die();
+        }
+    }
+    fn get_tag(&self) -> EntityTag {
+        EntityTag::Shot(self.player)
+    }
+}
@@ -1,17 +1,17 @@
-use ggez::graphics;
-use ggez::graphics::{Color, Drawable, Mesh};
-use ggez::graphics::{DrawMode, Point2};
-use ggez::Context;
+use engine::graphics::DrawPrimitives;
+use ggez::graphics::Point2;
 
 use entities::{Entity, EntityData, EntityTag};
 use math::VectorUtils;
 use messages::{Direction, Message, MessageSender};
 use palette::Palette;
 
+use playfield;
+
 use rand::{Rng, SeedableRng, StdRng};
 
-use W_HEIGHT;
-use W_WIDTH;
+// How much of the camera motion a star layer follows, per unit of distance.
+const PARALLAX: f32 = 0.025;
 
 pub struct Stars {
     entity_data: EntityData,
@@ -38,13 +38,22 @@ impl Stars {
         for _ in 1..80 {
             let x: f32 = rng.gen();
             let y: f32 = rng.gen();
-            let p = Point2::new(x * (W_WIDTH as f32), y * (W_HEIGHT as f32));
+            let p = playfield::at(x, y);
             let i: f32 = rng.gen();
             let i = 0.1 + i * 1.0;
             v.push((p, i));
         }
         v
     }
+
+    fn scroll(&mut self, offset: Point2) {
+        let wrap = |value: f32, size: f32| ((value % size) + size) % size;
+        for &mut (ref mut point, _) in self.stars.iter_mut() {
+            let npoint = point.add(offset);
+            point.x = wrap(npoint.x, playfield::width());
+            point.y = wrap(npoint.y, playfield::height());
+        }
+    }
 }
 
 impl Entity for Stars {
@@ -54,29 +63,18 @@ impl Entity for Stars {
     fn entity_data(&self) -> &EntityData {
         &self.entity_data
     }
-    fn update(&mut self, _ctx: &mut Context) {
+    fn update(&mut self, _delta_time: f32) {
         self.cycle += 0.1;
 
         if self.speed.norm() > 0.0 {
-            for &mut (ref mut point, _) in self.stars.iter_mut() {
-                let npoint = point.add(self.speed.mul(self.distance / 10.0));
-                point.x = npoint.x % W_WIDTH as f32;
-                point.y = npoint.y % W_HEIGHT as f32;
-            }
+            let offset = self.speed.mul(self.distance / 10.0);
+            self.scroll(offset);
         }
     }
-    fn render(&mut self, ctx: &mut Context) {
+    fn render(&self, surface: &mut DrawPrimitives) {
         let cycle = self.cycle;
 
-        graphics::set_color(ctx, Color::from(Palette::Light(0.0))).unwrap();
-        let polygon = [
-            Point2::new(0.0, -1.0),
-            Point2::new(0.0, 1.0),
-            Point2::new(0.0, 0.0),
-            Point2::new(-1.0, 0.0),
-            Point2::new(1.0, 0.0),
-        ];
-        let star = Mesh::new_polyline(ctx, DrawMode::Line(1.0), &polygon).unwrap();
+        surface.set_color(&Palette::Light(0.0));
 
         for pi in self.stars.iter() {
             let (p, i) = *pi;
@@ -86,7 +84,8 @@ impl Entity for Stars {
             if cycle % (10.0 * i) < i {
                 continue;
             }
-            star.draw(ctx, p, 0.0).unwrap();
+            surface.line(p.add(Point2::up()), p.add(Point2::down()), 1.0);
+            surface.line(p.add(Point2::left()), p.add(Point2::right()), 1.0);
         }
     }
     fn receive_message(&mut self, _sender: MessageSender, message: Message) {
@@ -97,10 +96,17 @@ impl Entity for Stars {
             Message::Stop => {
                 self.speed = Point2::new(0.0, 0.0);
             }
+            Message::CameraMoved(moved) => {
+                let offset = moved.mul(-self.distance * PARALLAX);
+                self.scroll(offset);
+            }
             _ => (),
         }
     }
     fn get_tag(&self) -> EntityTag {
         EntityTag::Stars
     }
+    fn screen_space(&self) -> bool {
+        true
+    }
 }
@@ -1,15 +1,21 @@
-use ggez::graphics;
-use ggez::graphics::Color;
-use ggez::graphics::{DrawMode, Point2};
-use ggez::Context;
+use engine::graphics::{DrawMode, DrawPrimitives};
+use ggez::graphics::Point2;
 
+use entities::shot::Shot;
 use entities::{Entity, EntityData, EntityTag, EntityTagPlayer};
 use messages::{Direction, Message, MessageSender};
 use palette::Palette;
 
 use math::VectorUtils;
 
-const PRECISION: f32 = 0.5;
+// Highest weapon level, one more shot per volley for each level.
+pub const MAX_WEAPON: u32 = 3;
+// Seconds between volleys while shooting is held.
+const FIRE_INTERVAL: f32 = 0.2;
+const SHOT_SPEED: f32 = 600.0;
+const SHOT_LIFETIME: f32 = 1.0;
+// Angle between the shots of a volley.
+const SHOT_SPREAD: f32 = 0.12;
 
 pub struct Twin {
     entity_data: EntityData,
@@ -18,6 +24,9 @@ pub struct Twin {
     player: Player,
     speed: f32,
     animation_speed: f32,
+    weapon: u32,
+    cooldown: f32,
+    shots: Vec<Shot>,
 }
 
 impl Entity for Twin {
@@ -27,17 +36,17 @@ impl Entity for Twin {
     fn entity_data(&self) -> &EntityData {
         &self.entity_data
     }
-    fn update(&mut self, _ctx: &mut Context) {}
-    fn render(&mut self, ctx: &mut Context) {
+    fn update(&mut self, delta_time: f32) {
         self.cycle += 0.1;
-        graphics::set_color(ctx, Color::from(Palette::Player)).unwrap();
-        graphics::circle(
-            ctx,
+        self.cooldown -= delta_time;
+    }
+    fn render(&self, surface: &mut DrawPrimitives) {
+        surface.set_color(&Palette::Player);
+        surface.circle(
             DrawMode::Fill,
             self.entity_data.pos,
             20.0 + (self.cycle * self.animation_speed).sin() * 2.0,
-            PRECISION,
-        ).unwrap();
+        );
     }
     fn receive_message(&mut self, _sender: MessageSender, message: Message) {
         match message {
@@ -45,14 +54,19 @@ impl Entity for Twin {
                 let pos = self.entity_data.pos.add(axis.mul(self.speed));
                 self.entity_data.pos.set(pos);
             }
+            Message::Shoot => self.shoot(),
+            Message::Weapon(level) => self.weapon = level.min(MAX_WEAPON).max(1),
             _ => (),
         }
     }
     fn get_tag(&self) -> EntityTag {
-        match self.player {
-            Player::One => EntityTag::Player(EntityTagPlayer::One),
-            Player::Two => EntityTag::Player(EntityTagPlayer::Two),
-        }
+        EntityTag::Player(self.tag_player())
+    }
+    fn take_spawned(&mut self) -> Vec<Box<Entity>> {
+        self.shots
+            .drain(..)
+            .map(|s| Box::new(s) as Box<Entity>)
+            .collect()
     }
 }
 
@@ -73,6 +87,36 @@ impl Twin {
             speed: 10.0,
             animation_speed: 2.0,
             player,
+            weapon: 1,
+            cooldown: 0.0,
+            shots: Vec::new(),
+        }
+    }
+
+    fn tag_player(&self) -> EntityTagPlayer {
+        match self.player {
+            Player::One => EntityTagPlayer::One,
+            Player::Two => EntityTagPlayer::Two,
+        }
+    }
+
+    // A volley of `weapon` shots fanning upwards.
+    fn shoot(&mut self) {
+        if self.cooldown > 0.0 {
+            return;
+        }
+        self.cooldown = FIRE_INTERVAL;
+        let middle = (self.weapon as f32 - 1.0) / 2.0;
+        for i in 0..self.weapon {
+            let angle = (i as f32 - middle) * SHOT_SPREAD;
+            let velocity = Point2::up().rotate(angle).mul(SHOT_SPEED);
+            let player = self.tag_player();
+            self.shots.push(Shot::new(
+                self.entity_data.pos,
+                velocity,
+                SHOT_LIFETIME,
+                player,
+            ));
         }
     }
 }
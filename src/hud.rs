@@ -0,0 +1,151 @@
+//! What the players need to know during play: each twin's score, lives and
+//! weapon level, the wave, and popups where things happen.
+//!
+//! The first twin's status sits in the top left corner of the playfield and
+//! the second's in the top right, the wave in between.
+
+use engine::graphics::{Color, DrawMode, DrawPrimitives, Transform};
+use ggez::graphics::Point2;
+
+use entities::twin::MAX_WEAPON;
+use palette::Palette;
+use playfield;
+
+const MARGIN: f32 = 12.0;
+// Width of each twin's status, the second one is right aligned.
+const STATUS_WIDTH: f32 = 90.0;
+const LINE_HEIGHT: f32 = 18.0;
+// Roughly how wide a character of the backend fonts is.
+const CHAR_WIDTH: f32 = 7.0;
+const LIFE_RADIUS: f32 = 5.0;
+const PIP_SIZE: f32 = 8.0;
+// How long popups last and how fast they float up.
+const POPUP_TIME: f32 = 0.8;
+const POPUP_RISE: f32 = 40.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TwinStatus {
+    pub score: u32,
+    pub lives: u32,
+    pub weapon: u32,
+}
+
+#[derive(Clone, Debug)]
+struct Popup {
+    text: String,
+    pos: Point2,
+    age: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Hud {
+    pub twins: Vec<TwinStatus>,
+    pub wave: u32,
+    popups: Vec<Popup>,
+    // Popups are placed in the world and follow it through the camera.
+    camera: Transform,
+}
+
+impl Hud {
+    pub fn new() -> Self {
+        Hud {
+            twins: Vec::new(),
+            wave: 1,
+            popups: Vec::new(),
+            camera: Transform::identity(),
+        }
+    }
+
+    // Shows `text` centered on `pos` in world coordinates for a moment.
+    pub fn popup(&mut self, text: &str, pos: Point2) {
+        self.popups.push(Popup {
+            text: text.to_string(),
+            pos,
+            age: 0.0,
+        });
+    }
+
+    pub fn popups(&self) -> usize {
+        self.popups.len()
+    }
+
+    pub fn update(&mut self, delta_time: f32, camera: Transform) {
+        self.camera = camera;
+        for popup in self.popups.iter_mut() {
+            popup.age += delta_time;
+            popup.pos.y -= POPUP_RISE * delta_time;
+        }
+        self.popups.retain(|popup| popup.age < POPUP_TIME);
+    }
+
+    pub fn render(&self, surface: &mut DrawPrimitives) {
+        for (i, status) in self.twins.iter().enumerate() {
+            let x = match i {
+                0 => MARGIN,
+                _ => playfield::width() - MARGIN - STATUS_WIDTH,
+            };
+            render_status(surface, status, Point2::new(x, MARGIN));
+        }
+
+        let wave = format!("WAVE {}", self.wave);
+        surface.set_color(&Palette::Light(0.5));
+        surface.text(
+            &wave,
+            Point2::new(centered(&wave, playfield::center().x), MARGIN),
+        );
+
+        for popup in self.popups.iter() {
+            let mut color = Palette::Light(1.0).into_rgba();
+            color[3] = 1.0 - popup.age / POPUP_TIME;
+            let pos = self.camera.apply(popup.pos);
+            surface.set_color(&color);
+            surface.text(
+                &popup.text,
+                Point2::new(centered(&popup.text, pos.x), pos.y - LINE_HEIGHT / 2.0),
+            );
+        }
+    }
+}
+
+// Score on top, then a dot per life and the weapon level as filled pips.
+fn render_status(surface: &mut DrawPrimitives, status: &TwinStatus, origin: Point2) {
+    surface.set_color(&Palette::Light(1.0));
+    surface.text(&format!("{:06}", status.score), origin);
+
+    let lives_y = origin.y + LINE_HEIGHT + LIFE_RADIUS;
+    surface.set_color(&Palette::Player);
+    for i in 0..status.lives {
+        let x = origin.x + LIFE_RADIUS + i as f32 * LIFE_RADIUS * 3.0;
+        surface.circle(DrawMode::Fill, Point2::new(x, lives_y), LIFE_RADIUS);
+    }
+
+    let pips_y = lives_y + LIFE_RADIUS * 2.0;
+    for i in 0..MAX_WEAPON {
+        let pip = Point2::new(origin.x + i as f32 * PIP_SIZE * 1.5, pips_y);
+        match i < status.weapon {
+            true => {
+                surface.set_color(&Palette::Light(1.0));
+                surface.rectangle(DrawMode::Fill, pip, PIP_SIZE, PIP_SIZE);
+            }
+            false => {
+                surface.set_color(&Palette::Light(0.0));
+                surface.rectangle(DrawMode::Line(1.0), pip, PIP_SIZE, PIP_SIZE);
+            }
+        }
+    }
+}
+
+fn centered(text: &str, x: f32) -> f32 {
+    x - text.chars().count() as f32 * CHAR_WIDTH / 2.0
+}
+
+#[test]
+fn popups_float_up_and_expire() {
+    let mut hud = Hud::new();
+    hud.popup("+100", Point2::new(10.0, 100.0));
+    hud.update(0.5, Transform::identity());
+    assert_eq!(hud.popups(), 1);
+    assert!(hud.popups[0].pos.y < 100.0);
+    hud.update(0.5, Transform::identity());
+    assert_eq!(hud.popups(), 0);
+}
@@ -0,0 +1,365 @@
+extern crate ggez;
+extern crate image;
+#[macro_use]
+extern crate log;
+extern crate petgraph;
+extern crate rand;
+
+use ggez::event::{Button, Keycode};
+use ggez::graphics::Point2;
+use ggez::*;
+use nalgebra as na;
+use rand::{SeedableRng, StdRng};
+use std::cmp::Ordering;
+use std::path::Path;
+
+pub mod bezier;
+pub mod bezier2;
+pub mod camera;
+pub mod color;
+pub mod controller;
+pub mod cutscene;
+pub mod debug;
+pub mod engine;
+pub mod entities;
+pub mod hud;
+pub mod math;
+pub mod menu;
+pub mod mekano;
+pub mod mekano_collider;
+pub mod mekano_format;
+pub mod mekano_ik;
+pub mod mekano_renderer;
+pub mod messages;
+pub mod palette;
+pub mod playfield;
+pub mod runner;
+pub mod screen;
+pub mod settings;
+pub mod software;
+pub mod states;
+pub mod svg;
+pub mod terminal;
+pub mod tween;
+pub mod world;
+
+use camera::Camera;
+use controller::Controller;
+use engine::graphics::{Draw, DrawPrimitives};
+use engine::logical::{Input, Update};
+use entities::{Entity, EntityId, EntityTag};
+use math::VectorUtils;
+use menu::MenuInput;
+use messages::{Message, MessageSender, SendMessageTo};
+use palette::{Palette, Theme};
+use settings::Settings;
+use states::menus;
+use states::menus::TitleState;
+use states::{StateStack, Transition};
+
+// Size of the logical playfield, and of the window when the game starts.
+pub const W_HEIGHT: u32 = 600;
+pub const W_WIDTH: u32 = 400;
+// Every run draws the same random numbers.
+const SEED: usize = 2;
+
+pub struct Game {
+    pub camera: Camera,
+    pub settings: Settings,
+    pub rng: StdRng,
+    // How much faster than real time everything plays.
+    time_scale: f32,
+    entities: Vec<(EntityId, Box<Entity>)>,
+    entity_id_counter: EntityId,
+    _currently_updated_entity_id: EntityId,
+}
+
+impl Game {
+    pub fn new() -> Self {
+        Game {
+            camera: Camera::new(),
+            settings: Settings::default(),
+            rng: StdRng::from_seed(&[SEED]),
+            time_scale: 1.0,
+            entities: Vec::new(),
+            entity_id_counter: 0,
+            _currently_updated_entity_id: 0,
+        }
+    }
+    // Back to how a new run starts: no entities, ids counting from one again
+    // and the same random numbers. Settings are kept.
+    pub fn reset(&mut self) {
+        let settings = self.settings.clone();
+        *self = Game {
+            settings,
+            ..Game::new()
+        };
+    }
+    pub fn delta_time(&self) -> f32 {
+        0.033 * self.time_scale
+    }
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale;
+    }
+    fn new_entity_id(&mut self) -> EntityId {
+        self.entity_id_counter += 1;
+        return self.entity_id_counter;
+    }
+    pub fn add_entity(&mut self, entity: Box<Entity>) -> EntityId {
+        let id = self.new_entity_id();
+        self.entities.push((id, entity));
+        self.entities.sort_by(|ref iea, ref ieb| {
+            let a = &iea.1;
+            let b = &ieb.1;
+            if a.z_order() > b.z_order() {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            }
+        });
+        id
+    }
+    pub fn remove_entity(&mut self, id: EntityId) {
+        self.entities.retain(|ie| ie.0 != id);
+    }
+    pub fn position_of(&self, id: EntityId) -> Option<Point2> {
+        self.entities
+            .iter()
+            .find(|ie| ie.0 == id)
+            .map(|ie| ie.1.get_pos())
+    }
+    pub fn set_position(&mut self, id: EntityId, pos: Point2) {
+        if let Some(ie) = self.entities.iter_mut().find(|ie| ie.0 == id) {
+            ie.1.set_pos(pos);
+        }
+    }
+    pub fn is_alive(&self, id: EntityId) -> bool {
+        self.entities.iter().any(|ie| ie.0 == id && ie.1.is_alive())
+    }
+    pub fn tagged(&self, tag: EntityTag) -> Vec<(EntityId, Point2)> {
+        self.entities
+            .iter()
+            .filter(|ie| ie.1.get_tag().suffices(tag))
+            .map(|ie| (ie.0, ie.1.get_pos()))
+            .collect()
+    }
+    pub fn positions(&self, tag: EntityTag) -> Vec<Point2> {
+        self.entities
+            .iter()
+            .filter(|ie| ie.1.get_tag().suffices(tag))
+            .map(|ie| ie.1.get_pos())
+            .collect()
+    }
+    pub fn nearest(&self, tag: EntityTag, pos: Point2) -> Option<Point2> {
+        self.entities
+            .iter()
+            .filter(|ie| ie.1.get_tag().suffices(tag))
+            .map(|ie| ie.1.get_pos())
+            .min_by(|a, b| {
+                let da = a.sub(pos).norm();
+                let db = b.sub(pos).norm();
+                da.partial_cmp(&db).unwrap_or(Ordering::Equal)
+            })
+    }
+    pub fn hit_query(
+        &self,
+        tag: EntityTag,
+        point: Point2,
+        radius: f32,
+    ) -> Option<(EntityId, usize)> {
+        self.entities
+            .iter()
+            .rev()
+            .filter(|ie| ie.1.is_alive() && ie.1.get_tag().suffices(tag))
+            .filter_map(|ie| ie.1.hit_test(point, radius).map(|node| (ie.0, node)))
+            .next()
+    }
+    pub fn update(&mut self) {
+        let delta_time = self.delta_time();
+        for i in self.entities.iter_mut() {
+            let (_id, ref mut entity) = *i;
+            entity.update(delta_time);
+        }
+        self.add_spawned_entities();
+        self.follow_attachments();
+        let trauma: f32 = self.entities.iter_mut().map(|ie| ie.1.take_trauma()).sum();
+        self.camera.add_trauma(trauma);
+        self.camera.update(delta_time);
+        let moved = self.camera.moved();
+        if moved.norm() > 0.0 {
+            self.send_message(EntityTag::Stars, Message::CameraMoved(moved));
+        }
+        self.entities.retain(move |ie| {
+            let (_id, ref entity) = *ie;
+            entity.is_alive()
+        });
+    }
+    pub fn render(&self, surface: &mut DrawPrimitives) {
+        surface.clear(&Palette::Black);
+        let camera = self.camera.transform();
+        for i in self.entities.iter() {
+            let (_id, ref entity) = *i;
+            if entity.screen_space() {
+                entity.render(surface);
+            } else {
+                surface.push_transform(camera);
+                entity.render(surface);
+                surface.pop_transform();
+            }
+        }
+    }
+    fn follow_attachments(&mut self) {
+        let targets: Vec<(usize, Option<Point2>)> = self.entities
+            .iter()
+            .enumerate()
+            .filter_map(|(i, ie)| ie.1.attached_to().map(|id| (i, self.position_of(id))))
+            .collect();
+        for (i, target) in targets {
+            let entity = &mut self.entities[i].1;
+            match target {
+                Some(pos) => entity.set_pos(pos),
+                None => entity.receive_message(MessageSender::God, Message::Stop),
+            }
+        }
+    }
+    fn add_spawned_entities(&mut self) {
+        let spawned: Vec<Box<Entity>> = self.entities
+            .iter_mut()
+            .flat_map(|ie| ie.1.take_spawned())
+            .collect();
+        for entity in spawned {
+            self.add_entity(entity);
+        }
+    }
+}
+
+impl SendMessageTo<EntityId> for Game {
+    fn send_message(&mut self, target_id: EntityId, message: Message) {
+        for ie in self.entities.iter_mut() {
+            let (id, ref mut entity) = *ie;
+            if id == target_id {
+                entity.receive_message(MessageSender::God, message);
+            }
+        }
+    }
+}
+
+impl SendMessageTo<EntityTag> for Game {
+    fn send_message(&mut self, target_tag: EntityTag, message: Message) {
+        for ie in self.entities.iter_mut() {
+            let (_, ref mut entity) = *ie;
+            if entity.get_tag().suffices(target_tag) {
+                entity.receive_message(MessageSender::God, message);
+            }
+        }
+    }
+}
+
+/// The game itself: the intro, the twins and the enemies, ready to be run by
+/// any backend through `runner`.
+pub struct Twins {
+    game: Game,
+    controller: Controller,
+    states: StateStack,
+    t: f32,
+}
+
+impl Twins {
+    pub fn new() -> Self {
+        let mut game = Game::new();
+        game.settings = Settings::load();
+        let themes = Theme::available(Path::new("themes"));
+        if let Some(theme) = themes.into_iter().find(|t| t.name == game.settings.theme) {
+            palette::set_theme(theme);
+        }
+        let mut states = StateStack::new();
+        states.apply(&mut game, Transition::Push(Box::new(TitleState::new())));
+        Twins {
+            game,
+            controller: Controller::new(),
+            states,
+            t: 0.0,
+        }
+    }
+}
+
+impl Twins {
+    // Fast forward speeds up the whole cutscene, entities included.
+    fn control_cutscene(&mut self) {
+        let skip = self.controller.take_skip();
+        let mut time_scale = 1.0;
+        if let Some(timeline) = self.states.cutscene_mut() {
+            let settings = &mut self.game.settings;
+            if skip && !settings.skip_intro {
+                settings.skip_intro = true;
+                settings.save();
+            }
+            if settings.skip_intro {
+                timeline.skip(&mut self.game);
+            }
+            time_scale = self.controller.cutscene_speed();
+        }
+        self.game.set_time_scale(time_scale);
+    }
+}
+
+impl<C> Update<C> for Twins {
+    fn update(&mut self, _backend: &mut C) {
+        self.t += self.game.delta_time();
+        if self.states.runs_game() {
+            self.controller.update(&mut self.game);
+            self.control_cutscene();
+            self.game.update();
+        }
+        self.states.update(&mut self.game);
+    }
+
+    fn finished(&self) -> bool {
+        self.states.finished()
+    }
+}
+
+impl<S> Draw<S> for Twins
+where
+    S: DrawPrimitives,
+{
+    fn draw(&self, surface: &mut S) {
+        self.game.render(surface);
+        self.states.render(surface);
+    }
+}
+
+// Menus get the keys first, the controller whatever they leave.
+impl Input<Keycode> for Twins {
+    fn key_down(&mut self, keycode: Keycode) {
+        if let Keycode::F10 = keycode {
+            menus::next_theme(&mut self.game.settings);
+            return;
+        }
+        if let Some(input) = MenuInput::from_keycode(keycode) {
+            if self.states.input(&mut self.game, input) {
+                return;
+            }
+        }
+        self.controller.key_down_event(&mut self.game, keycode);
+    }
+
+    fn key_up(&mut self, keycode: Keycode) {
+        self.controller.key_up_event(&mut self.game, keycode);
+    }
+}
+
+impl Input<Button> for Twins {
+    fn key_down(&mut self, button: Button) {
+        if let Some(input) = MenuInput::from_button(button) {
+            if self.states.input(&mut self.game, input) {
+                return;
+            }
+        }
+        self.controller.button_down_event(&mut self.game, button);
+    }
+
+    fn key_up(&mut self, button: Button) {
+        self.controller.button_up_event(&mut self.game, button);
+    }
+}
@@ -1,6 +1,6 @@
 use ggez::graphics::Point2;
 use rand::distributions::{Normal, Sample};
-use rand::Rng;
+use rand::{Rng, SeedableRng, StdRng};
 use std::marker::Sized;
 use std::ops::{Add, Mul, Sub};
 
@@ -128,6 +128,11 @@ impl Wavize for f32 {
     }
 }
 
+// A generator of its own seeded from `rng`, so runs seeded alike stay alike.
+pub fn fork(rng: &mut StdRng) -> StdRng {
+    StdRng::from_seed(&[rng.gen::<usize>()])
+}
+
 pub trait Randomize<RNG>
 where
     Self: Sized + Add<Self> + Mul<Self> + Sub<Self>,
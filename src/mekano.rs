@@ -1,4 +1,6 @@
-#[derive(Debug)]
+use std::mem;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Mekano<Data> {
     End(Data),
     Segment(Data, Box<Mekano<Data>>),
@@ -20,4 +22,141 @@ impl<Data> Mekano<Data> {
             &mut Mekano::Split(ref mut d, _, _) => d,
         }
     }
+    pub fn children<'a>(&'a self) -> Vec<&'a Mekano<Data>> {
+        match self {
+            &Mekano::End(_) => vec![],
+            &Mekano::Segment(_, ref j) => vec![j],
+            &Mekano::Split(_, ref j1, ref j2) => vec![j1, j2],
+        }
+    }
+    pub fn for_each_mut<F>(&mut self, f: &mut F)
+    where
+        F: FnMut(&mut Data),
+    {
+        match self {
+            &mut Mekano::End(ref mut d) => f(d),
+            &mut Mekano::Segment(ref mut d, ref mut j) => {
+                f(d);
+                j.for_each_mut(f);
+            }
+            &mut Mekano::Split(ref mut d, ref mut j1, ref mut j2) => {
+                f(d);
+                j1.for_each_mut(f);
+                j2.for_each_mut(f);
+            }
+        }
+    }
+    // Nodes are numbered in pre-order, the root being 0.
+    pub fn nodes<'a>(&'a self) -> Vec<&'a Mekano<Data>> {
+        let mut nodes = vec![self];
+        for child in self.children() {
+            nodes.extend(child.nodes());
+        }
+        nodes
+    }
+    pub fn len(&self) -> usize {
+        1 + self.children().iter().map(|c| c.len()).sum::<usize>()
+    }
+    pub fn get_mut<'a>(&'a mut self, index: usize) -> Option<&'a mut Mekano<Data>> {
+        if index == 0 {
+            return Some(self);
+        }
+        let mut offset = 1;
+        match self {
+            &mut Mekano::End(_) => None,
+            &mut Mekano::Segment(_, ref mut j) => j.get_mut(index - offset),
+            &mut Mekano::Split(_, ref mut j1, ref mut j2) => {
+                if index - offset < j1.len() {
+                    j1.get_mut(index - offset)
+                } else {
+                    offset += j1.len();
+                    j2.get_mut(index - offset)
+                }
+            }
+        }
+    }
+    // Removes the subtree at `index`. Its parent loses a branch, so a split
+    // becomes a segment and a segment becomes an end. The root can't be detached.
+    pub fn detach(&mut self, index: usize) -> Option<Mekano<Data>>
+    where
+        Data: Clone,
+    {
+        if index == 0 || index >= self.len() {
+            return None;
+        }
+        let mut offset = 1;
+        for (branch, child_len) in self
+            .children()
+            .iter()
+            .map(|c| c.len())
+            .enumerate()
+            .collect::<Vec<_>>()
+        {
+            if index == offset {
+                return Some(self.detach_branch(branch));
+            }
+            if index < offset + child_len {
+                return match self {
+                    &mut Mekano::Segment(_, ref mut j) => j.detach(index - offset),
+                    &mut Mekano::Split(_, ref mut j1, _) if branch == 0 => {
+                        j1.detach(index - offset)
+                    }
+                    &mut Mekano::Split(_, _, ref mut j2) => j2.detach(index - offset),
+                    &mut Mekano::End(_) => None,
+                };
+            }
+            offset += child_len;
+        }
+        None
+    }
+    fn detach_branch(&mut self, branch: usize) -> Mekano<Data>
+    where
+        Data: Clone,
+    {
+        let placeholder = Mekano::End(self.data().clone());
+        match mem::replace(self, placeholder) {
+            Mekano::End(d) => {
+                *self = Mekano::End(d);
+                panic!("Mekano::End has no branches to detach")
+            }
+            Mekano::Segment(d, j) => {
+                *self = Mekano::End(d);
+                *j
+            }
+            Mekano::Split(d, j1, j2) => {
+                if branch == 0 {
+                    *self = Mekano::Segment(d, j2);
+                    *j1
+                } else {
+                    *self = Mekano::Segment(d, j1);
+                    *j2
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn mekano_detach_subtree() {
+    let mut m = Mekano::Split(
+        0,
+        Box::new(Mekano::Segment(1, Box::new(Mekano::End(2)))),
+        Box::new(Mekano::Split(
+            3,
+            Box::new(Mekano::End(4)),
+            Box::new(Mekano::End(5)),
+        )),
+    );
+    assert_eq!(m.len(), 6);
+    assert_eq!(m.detach(0), None);
+    assert_eq!(m.detach(4), Some(Mekano::End(4)));
+    assert_eq!(
+        m.detach(1),
+        Some(Mekano::Segment(1, Box::new(Mekano::End(2))))
+    );
+    assert_eq!(
+        m,
+        Mekano::Segment(0, Box::new(Mekano::Segment(3, Box::new(Mekano::End(5)))))
+    );
+    assert_eq!(m.get_mut(2).map(|n| *n.data()), Some(5));
 }
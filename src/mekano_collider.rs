@@ -0,0 +1,108 @@
+use ggez::graphics::Point2;
+use math::VectorUtils;
+use mekano::Mekano;
+use mekano_renderer::{outline, world_transforms, Data, Shape};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collider {
+    pub node: usize,
+    pub origin: Point2,
+    pub rotation: f32,
+    pub shape: Shape,
+}
+
+impl Collider {
+    pub fn hits(&self, point: Point2, radius: f32) -> bool {
+        let local = point.sub(self.origin).rotate(-self.rotation);
+        match self.shape {
+            Shape::Circle(r) => local.norm() <= r + radius,
+            Shape::Ellipse(rx, ry) => {
+                let (rx, ry) = (rx + radius, ry + radius);
+                (local.x / rx).powi(2) + (local.y / ry).powi(2) <= 1.0
+            }
+            Shape::None => false,
+            shape => match outline(shape) {
+                Some(corners) => polygon_hits(&corners, local, radius),
+                None => false,
+            },
+        }
+    }
+}
+
+// Colliders for every shaped node, in world space.
+pub fn colliders<D>(mekano: &Mekano<D>) -> Vec<Collider>
+where
+    D: Data,
+{
+    mekano
+        .nodes()
+        .iter()
+        .zip(world_transforms(mekano))
+        .enumerate()
+        .filter(|&(_, (node, _))| node.data().shape() != Shape::None)
+        .map(|(index, (node, (origin, rotation)))| Collider {
+            node: index,
+            origin,
+            rotation,
+            shape: node.data().shape(),
+        })
+        .collect()
+}
+
+// Node struck by a circle at `point`. Nodes drawn later are on top, so they win.
+pub fn hit<D>(mekano: &Mekano<D>, point: Point2, radius: f32) -> Option<usize>
+where
+    D: Data,
+{
+    colliders(mekano)
+        .iter()
+        .rev()
+        .find(|c| c.hits(point, radius))
+        .map(|c| c.node)
+}
+
+fn polygon_hits(corners: &[Point2], point: Point2, radius: f32) -> bool {
+    let edges = corners.iter().zip(corners.iter().cycle().skip(1));
+    let mut inside = true;
+    for (a, b) in edges {
+        let edge = b.sub(*a);
+        let to_point = point.sub(*a);
+        if edge.x * to_point.y - edge.y * to_point.x < 0.0 {
+            inside = false;
+        }
+        let t = ((to_point.x * edge.x + to_point.y * edge.y) / edge.norm().powi(2))
+            .max(0.0)
+            .min(1.0);
+        if point.sub(a.add(edge.mul(t))).norm() <= radius {
+            return true;
+        }
+    }
+    inside
+}
+
+#[cfg(test)]
+struct TestNode(Shape, f32);
+
+#[cfg(test)]
+impl Data for TestNode {
+    fn shape(&self) -> Shape {
+        self.0
+    }
+    fn length(&self) -> f32 {
+        self.1
+    }
+}
+
+#[test]
+fn hit_reports_struck_node() {
+    let body = Mekano::Split(
+        TestNode(Shape::Circle(10.0), 0.0),
+        Box::new(Mekano::End(TestNode(Shape::None, 5.0))),
+        Box::new(Mekano::End(TestNode(Shape::Rectangle(10.0, 4.0), 20.0))),
+    );
+    assert_eq!(colliders(&body).len(), 2);
+    assert_eq!(hit(&body, Point2::new(0.0, 9.0), 0.0), Some(0));
+    assert_eq!(hit(&body, Point2::new(24.0, 1.0), 0.0), Some(2));
+    assert_eq!(hit(&body, Point2::new(20.0, 2.5), 1.0), Some(2));
+    assert_eq!(hit(&body, Point2::new(20.0, 8.0), 1.0), None);
+}
@@ -0,0 +1,393 @@
+//! Text format for `Mekano` trees.
+//!
+//! Every line is a node: its kind (`end`, `segment` or `split`) followed by
+//! `key=value` fields. Children go on the following lines, indented deeper
+//! than their parent. Blank lines and lines starting with `#` are ignored.
+//!
+//! ```text
+//! # a small boss
+//! split shape=circle(20) origin=(200,100)
+//!     segment shape=circle(8) length=30 angle=0.6 animation=(2,0.3,0)
+//!         end shape=ellipse(6,10) length=18
+//!     end shape=rombus(10,14) length=30 angle=-0.6
+//! ```
+
+use ggez::graphics::Point2;
+use mekano::Mekano;
+use mekano_renderer::Shape;
+
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::path::Path;
+
+const INDENT: &str = "    ";
+
+pub trait Fields
+where
+    Self: Sized,
+{
+    fn to_fields(&self) -> Vec<(&'static str, String)>;
+    // Keys it doesn't know are an error, see `check_keys`.
+    fn from_fields(fields: &[(String, String)]) -> Result<Self, String>;
+}
+
+#[derive(Debug)]
+pub enum FormatError {
+    Io(io::Error),
+    Syntax(usize, String),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &FormatError::Io(ref e) => write!(f, "{}", e),
+            &FormatError::Syntax(line, ref message) => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl From<io::Error> for FormatError {
+    fn from(e: io::Error) -> Self {
+        FormatError::Io(e)
+    }
+}
+
+pub fn save<D>(mekano: &Mekano<D>) -> String
+where
+    D: Fields,
+{
+    let mut out = String::new();
+    save_node(mekano, 0, &mut out);
+    out
+}
+
+fn save_node<D>(node: &Mekano<D>, depth: usize, out: &mut String)
+where
+    D: Fields,
+{
+    for _ in 0..depth {
+        out.push_str(INDENT);
+    }
+    out.push_str(match node {
+        &Mekano::End(_) => "end",
+        &Mekano::Segment(_, _) => "segment",
+        &Mekano::Split(_, _, _) => "split",
+    });
+    for (key, value) in node.data().to_fields() {
+        out.push_str(&format!(" {}={}", key, value));
+    }
+    out.push('\n');
+    for child in node.children() {
+        save_node(child, depth + 1, out);
+    }
+}
+
+pub fn save_file<D>(mekano: &Mekano<D>, path: &Path) -> Result<(), FormatError>
+where
+    D: Fields,
+{
+    let mut file = File::create(path)?;
+    file.write_all(save(mekano).as_bytes())?;
+    Ok(())
+}
+
+struct Line {
+    number: usize,
+    indent: usize,
+    kind: String,
+    fields: Vec<(String, String)>,
+}
+
+pub fn load<D>(text: &str) -> Result<Mekano<D>, FormatError>
+where
+    D: Fields,
+{
+    let lines = tokenize(text)?;
+    if lines.is_empty() {
+        return Err(FormatError::Syntax(0, "no nodes found".to_string()));
+    }
+    let mut next = 0;
+    let root = load_node(&lines, &mut next)?;
+    if let Some(line) = lines.get(next) {
+        return Err(FormatError::Syntax(
+            line.number,
+            "a model can only have one root node".to_string(),
+        ));
+    }
+    Ok(root)
+}
+
+pub fn load_file<D>(path: &Path) -> Result<Mekano<D>, FormatError>
+where
+    D: Fields,
+{
+    let mut text = String::new();
+    File::open(path)?.read_to_string(&mut text)?;
+    load(&text)
+}
+
+fn tokenize(text: &str) -> Result<Vec<Line>, FormatError> {
+    let mut lines = Vec::new();
+    for (i, raw) in text.lines().enumerate() {
+        let number = i + 1;
+        let content = raw.trim_start();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+        let indent = &raw[..raw.len() - content.len()];
+        if indent.contains('\t') {
+            return Err(FormatError::Syntax(
+                number,
+                "use spaces for indentation".to_string(),
+            ));
+        }
+        let mut words = content.split_whitespace();
+        let kind = words.next().unwrap().to_string();
+        let mut fields = Vec::new();
+        for word in words {
+            let mut pair = word.splitn(2, '=');
+            match (pair.next(), pair.next()) {
+                (Some(key), Some(value)) if !key.is_empty() => {
+                    fields.push((key.to_string(), value.to_string()))
+                }
+                _ => {
+                    return Err(FormatError::Syntax(
+                        number,
+                        format!("expected key=value, found '{}'", word),
+                    ))
+                }
+            }
+        }
+        lines.push(Line {
+            number,
+            indent: indent.len(),
+            kind,
+            fields,
+        });
+    }
+    Ok(lines)
+}
+
+fn load_node<D>(lines: &[Line], next: &mut usize) -> Result<Mekano<D>, FormatError>
+where
+    D: Fields,
+{
+    let line = &lines[*next];
+    *next += 1;
+
+    let data = D::from_fields(&line.fields).map_err(|e| FormatError::Syntax(line.number, e))?;
+
+    let mut children = Vec::new();
+    let mut child_indent = None;
+    while let Some(child) = lines.get(*next) {
+        if child.indent <= line.indent {
+            break;
+        }
+        match child_indent {
+            None => child_indent = Some(child.indent),
+            Some(indent) if indent != child.indent => {
+                return Err(FormatError::Syntax(
+                    child.number,
+                    "inconsistent indentation".to_string(),
+                ))
+            }
+            _ => {}
+        }
+        children.push(load_node(lines, next)?);
+    }
+
+    let expected = match line.kind.as_str() {
+        "end" => 0,
+        "segment" => 1,
+        "split" => 2,
+        kind => {
+            return Err(FormatError::Syntax(
+                line.number,
+                format!("unknown node kind '{}'", kind),
+            ))
+        }
+    };
+    if children.len() != expected {
+        return Err(FormatError::Syntax(
+            line.number,
+            format!(
+                "'{}' needs {} children, found {}",
+                line.kind,
+                expected,
+                children.len()
+            ),
+        ));
+    }
+
+    let mut children = children.into_iter().map(Box::new);
+    Ok(match expected {
+        0 => Mekano::End(data),
+        1 => Mekano::Segment(data, children.next().unwrap()),
+        _ => Mekano::Split(data, children.next().unwrap(), children.next().unwrap()),
+    })
+}
+
+pub fn field<'a>(fields: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    fields
+        .iter()
+        .rev()
+        .find(|f| f.0 == key)
+        .map(|f| f.1.as_str())
+}
+
+// Fails listing every key not in `known`, so a typo doesn't silently fall
+// back to the default.
+pub fn check_keys(fields: &[(String, String)], known: &[&str]) -> Result<(), String> {
+    let unknown: Vec<&str> = fields
+        .iter()
+        .map(|f| f.0.as_str())
+        .filter(|key| !known.contains(key))
+        .collect();
+    match unknown.is_empty() {
+        true => Ok(()),
+        false => Err(format!("unknown fields {}", unknown.join(", "))),
+    }
+}
+
+pub fn format_f32(value: f32) -> String {
+    format!("{}", value)
+}
+
+pub fn parse_f32(value: &str) -> Result<f32, String> {
+    value
+        .parse::<f32>()
+        .map_err(|_| format!("'{}' is not a number", value))
+}
+
+fn parse_tuple(value: &str) -> Result<Vec<f32>, String> {
+    if !value.starts_with('(') || !value.ends_with(')') {
+        return Err(format!("expected (a,b,...), found '{}'", value));
+    }
+    value[1..value.len() - 1]
+        .split(',')
+        .map(parse_f32)
+        .collect()
+}
+
+fn format_tuple(values: &[f32]) -> String {
+    let values: Vec<String> = values.iter().map(|v| format_f32(*v)).collect();
+    format!("({})", values.join(","))
+}
+
+pub fn format_point(point: Point2) -> String {
+    format_tuple(&[point.x, point.y])
+}
+
+pub fn parse_point(value: &str) -> Result<Point2, String> {
+    match parse_tuple(value)?.as_slice() {
+        &[x, y] => Ok(Point2::new(x, y)),
+        _ => Err(format!("expected (x,y), found '{}'", value)),
+    }
+}
+
+pub fn format_pair(values: (f32, f32)) -> String {
+    format_tuple(&[values.0, values.1])
+}
+
+pub fn parse_pair(value: &str) -> Result<(f32, f32), String> {
+    match parse_tuple(value)?.as_slice() {
+        &[a, b] => Ok((a, b)),
+        _ => Err(format!("expected (a,b), found '{}'", value)),
+    }
+}
+
+pub fn format_triple(values: (f32, f32, f32)) -> String {
+    format_tuple(&[values.0, values.1, values.2])
+}
+
+pub fn parse_triple(value: &str) -> Result<(f32, f32, f32), String> {
+    match parse_tuple(value)?.as_slice() {
+        &[a, b, c] => Ok((a, b, c)),
+        _ => Err(format!("expected (a,b,c), found '{}'", value)),
+    }
+}
+
+pub fn format_shape(shape: Shape) -> String {
+    match shape {
+        Shape::Circle(r) => format!("circle{}", format_tuple(&[r])),
+        Shape::Ellipse(w, h) => format!("ellipse{}", format_tuple(&[w, h])),
+        Shape::Sqare(s) => format!("square{}", format_tuple(&[s])),
+        Shape::Rectangle(w, h) => format!("rectangle{}", format_tuple(&[w, h])),
+        Shape::Rombus(w, h) => format!("rombus{}", format_tuple(&[w, h])),
+        Shape::None => "none".to_string(),
+    }
+}
+
+pub fn parse_shape(value: &str) -> Result<Shape, String> {
+    if value == "none" {
+        return Ok(Shape::None);
+    }
+    let open = value
+        .find('(')
+        .ok_or_else(|| format!("'{}' is not a shape", value))?;
+    let args = parse_tuple(&value[open..])?;
+    match (&value[..open], args.as_slice()) {
+        ("circle", &[r]) => Ok(Shape::Circle(r)),
+        ("ellipse", &[w, h]) => Ok(Shape::Ellipse(w, h)),
+        ("square", &[s]) => Ok(Shape::Sqare(s)),
+        ("rectangle", &[w, h]) => Ok(Shape::Rectangle(w, h)),
+        ("rombus", &[w, h]) => Ok(Shape::Rombus(w, h)),
+        _ => Err(format!("'{}' is not a shape", value)),
+    }
+}
+
+#[cfg(test)]
+#[derive(Debug, Clone, PartialEq)]
+struct TestData(Shape, f32);
+
+#[cfg(test)]
+impl Fields for TestData {
+    fn to_fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("shape", format_shape(self.0)),
+            ("angle", format_f32(self.1)),
+        ]
+    }
+    fn from_fields(fields: &[(String, String)]) -> Result<Self, String> {
+        check_keys(fields, &["shape", "angle"])?;
+        Ok(TestData(
+            parse_shape(field(fields, "shape").unwrap_or("none"))?,
+            parse_f32(field(fields, "angle").unwrap_or("0"))?,
+        ))
+    }
+}
+
+#[test]
+fn mekano_round_trip() {
+    let m = Mekano::Split(
+        TestData(Shape::Circle(20.0), 0.1),
+        Box::new(Mekano::Segment(
+            TestData(Shape::Rombus(3.5, 1.0 / 3.0), -0.7),
+            Box::new(Mekano::End(TestData(Shape::None, 1e-7))),
+        )),
+        Box::new(Mekano::End(TestData(Shape::Ellipse(4.0, 9.25), 3.1415927))),
+    );
+    let text = save(&m);
+    let loaded: Mekano<TestData> = load(&text).unwrap();
+    assert_eq!(loaded, m);
+    assert_eq!(save(&loaded), text);
+}
+
+#[test]
+fn mekano_load_checks_children() {
+    let text = "split shape=circle(2)\n    end\n";
+    match load::<TestData>(text) {
+        Err(FormatError::Syntax(1, _)) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn mekano_load_rejects_unknown_fields() {
+    match load::<TestData>("end shape=circle(2) angel=1 lenght=3\n") {
+        Err(FormatError::Syntax(1, message)) => assert!(message.ends_with("angel, lenght")),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
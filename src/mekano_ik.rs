@@ -0,0 +1,140 @@
+use ggez::graphics::Point2;
+use math::VectorUtils;
+use mekano::Mekano;
+use mekano_renderer::{world_transforms, Data};
+
+const TOLERANCE: f32 = 0.5;
+
+pub trait Joint: Data {
+    fn angle(&self) -> f32;
+    fn set_angle(&mut self, angle: f32);
+    fn limits(&self) -> (f32, f32);
+}
+
+// Heads of every chain of segments, the nodes whose parent isn't a segment.
+pub fn chains<D>(mekano: &Mekano<D>) -> Vec<usize> {
+    let mut heads = Vec::new();
+    collect_heads(mekano, 0, false, &mut heads);
+    heads
+}
+
+fn collect_heads<D>(
+    node: &Mekano<D>,
+    index: usize,
+    parent_is_segment: bool,
+    heads: &mut Vec<usize>,
+) -> usize {
+    let is_segment = match node {
+        &Mekano::Segment(_, _) => true,
+        _ => false,
+    };
+    if is_segment && !parent_is_segment {
+        heads.push(index);
+    }
+    let mut next = index + 1;
+    for child in node.children() {
+        next = collect_heads(child, next, is_segment, heads);
+    }
+    next
+}
+
+// Cyclic coordinate descent over the segment chain starting at `head`. Only
+// rotations change, so the chain keeps the lengths of its nodes. Returns
+// whether the tip of the chain got close enough to the target.
+pub fn reach<D>(mekano: &mut Mekano<D>, head: usize, target: Point2, iterations: usize) -> bool
+where
+    D: Joint,
+{
+    let joints = chain(mekano, head);
+    let tip = match joints.last() {
+        Some(&tip) => tip,
+        None => return false,
+    };
+    for _ in 0..iterations {
+        for &joint in joints.iter().rev().skip(1) {
+            let transforms = world_transforms(mekano);
+            let origin = transforms[joint].0;
+            let end = transforms[tip].0;
+            if end.sub(target).norm() < TOLERANCE {
+                return true;
+            }
+            let delta = angle_between(end.sub(origin), target.sub(origin));
+            if let Some(node) = mekano.get_mut(joint) {
+                let data = node.data_mut();
+                let (min, max) = data.limits();
+                let angle = (data.angle() + delta).max(min).min(max);
+                data.set_angle(angle);
+            }
+        }
+    }
+    let end = world_transforms(mekano)[tip].0;
+    end.sub(target).norm() < TOLERANCE
+}
+
+fn chain<D>(mekano: &Mekano<D>, head: usize) -> Vec<usize> {
+    let nodes = mekano.nodes();
+    let mut joints = Vec::new();
+    let mut index = head;
+    while let Some(node) = nodes.get(index) {
+        joints.push(index);
+        match *node {
+            &Mekano::Segment(_, _) => index += 1,
+            _ => break,
+        }
+    }
+    joints
+}
+
+fn angle_between(from: Point2, to: Point2) -> f32 {
+    use std::f32::consts::PI;
+    let mut delta = to.y.atan2(to.x) - from.y.atan2(from.x);
+    while delta > PI {
+        delta -= 2.0 * PI;
+    }
+    while delta < -PI {
+        delta += 2.0 * PI;
+    }
+    delta
+}
+
+#[cfg(test)]
+struct TestJoint(f32, f32);
+
+#[cfg(test)]
+impl Data for TestJoint {
+    fn rotation(&self) -> f32 {
+        self.0
+    }
+    fn length(&self) -> f32 {
+        self.1
+    }
+}
+
+#[cfg(test)]
+impl Joint for TestJoint {
+    fn angle(&self) -> f32 {
+        self.0
+    }
+    fn set_angle(&mut self, angle: f32) {
+        self.0 = angle;
+    }
+    fn limits(&self) -> (f32, f32) {
+        (-2.0, 2.0)
+    }
+}
+
+#[test]
+fn reach_target_with_arm() {
+    let mut arm = Mekano::Segment(
+        TestJoint(0.0, 0.0),
+        Box::new(Mekano::Segment(
+            TestJoint(0.0, 10.0),
+            Box::new(Mekano::End(TestJoint(0.0, 10.0))),
+        )),
+    );
+    assert_eq!(chains(&arm), vec![0]);
+    assert!(reach(&mut arm, 0, Point2::new(5.0, 12.0), 20));
+    let transforms = world_transforms(&arm);
+    assert!((transforms[1].0.norm() - 10.0).abs() < 0.001);
+    assert!(!reach(&mut arm, 0, Point2::new(30.0, 0.0), 20));
+}
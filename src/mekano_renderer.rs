@@ -1,13 +1,12 @@
-use ggez::graphics;
-use ggez::graphics::{DrawMode, Point2};
-use ggez::Context;
+use engine::graphics::{DrawMode, DrawPrimitives, Transform};
+use ggez::graphics::Point2;
 use math::VectorUtils;
 use mekano::Mekano;
 
 pub trait Render {
     type Data: Data;
 
-    fn render(&self, ctx: &mut Context);
+    fn render(&self, surface: &mut DrawPrimitives);
 }
 
 pub trait Data {
@@ -17,11 +16,16 @@ pub trait Data {
     fn rotation(&self) -> f32 {
         0.0
     }
+    // Distance to the parent joint, along the parent rotation. Ignored on the root.
+    fn length(&self) -> f32 {
+        0.0
+    }
     fn shape(&self) -> Shape {
         Shape::None
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Shape {
     Circle(f32),
     Ellipse(f32, f32),
@@ -37,24 +41,87 @@ where
 {
     type Data = D;
 
-    fn render(&self, ctx: &mut Context) {
-        {
-            const tolerance: f32 = 5.0;
-            let data = self.data();
-            let shape = data.shape();
-            let origin = data.origin();
-
-            match shape {
-                Shape::Circle(radius) => {
-                    graphics::circle(ctx, DrawMode::Fill, origin, radius, tolerance).unwrap();
-                }
-                _ => {}
-            }
+    fn render(&self, surface: &mut DrawPrimitives) {
+        let origin = self.data().origin();
+        surface.push_transform(Transform::translate(origin.x, origin.y));
+        render_node(self, surface);
+        surface.pop_transform();
+    }
+}
+
+// Draws the node around the local origin, children are placed relative to it.
+fn render_node<D>(node: &Mekano<D>, surface: &mut DrawPrimitives)
+where
+    D: Data,
+{
+    let center = Point2::new(0.0, 0.0);
+    surface.push_transform(Transform::rotate(node.data().rotation()));
+    match node.data().shape() {
+        Shape::Circle(radius) => {
+            surface.circle(DrawMode::Fill, center, radius);
+        }
+        Shape::Ellipse(width, height) => {
+            surface.ellipse(DrawMode::Fill, center, width, height);
         }
-        match self {
-            &Mekano::End(ref d) => {}
-            &Mekano::Segment(ref d, ref j) => {}
-            &Mekano::Split(ref d, ref j1, ref j2) => {}
+        shape => {
+            if let Some(points) = outline(shape) {
+                surface.polygon(DrawMode::Fill, &points);
+            }
         }
     }
+    for child in node.children() {
+        surface.push_transform(Transform::translate(child.data().length(), 0.0));
+        render_node(child, surface);
+        surface.pop_transform();
+    }
+    surface.pop_transform();
+}
+
+// Corners of the polygonal shapes, centered on the node and unrotated.
+pub fn outline(shape: Shape) -> Option<Vec<Point2>> {
+    let rectangle = |w: f32, h: f32| {
+        vec![
+            Point2::new(-w / 2.0, -h / 2.0),
+            Point2::new(w / 2.0, -h / 2.0),
+            Point2::new(w / 2.0, h / 2.0),
+            Point2::new(-w / 2.0, h / 2.0),
+        ]
+    };
+    match shape {
+        Shape::Sqare(side) => Some(rectangle(side, side)),
+        Shape::Rectangle(width, height) => Some(rectangle(width, height)),
+        Shape::Rombus(width, height) => Some(vec![
+            Point2::new(0.0, -height / 2.0),
+            Point2::new(width / 2.0, 0.0),
+            Point2::new(0.0, height / 2.0),
+            Point2::new(-width / 2.0, 0.0),
+        ]),
+        _ => None,
+    }
+}
+
+// World position and rotation of every node, in the same order as `Mekano::nodes`.
+pub fn world_transforms<D>(mekano: &Mekano<D>) -> Vec<(Point2, f32)>
+where
+    D: Data,
+{
+    let mut transforms = Vec::new();
+    push_transforms(mekano, mekano.data().origin(), 0.0, &mut transforms);
+    transforms
+}
+
+fn push_transforms<D>(
+    node: &Mekano<D>,
+    origin: Point2,
+    parent_rotation: f32,
+    transforms: &mut Vec<(Point2, f32)>,
+) where
+    D: Data,
+{
+    let rotation = parent_rotation + node.data().rotation();
+    transforms.push((origin, rotation));
+    for child in node.children() {
+        let child_origin = origin.add(Point2::new(child.data().length(), 0.0).rotate(rotation));
+        push_transforms(child, child_origin, rotation, transforms);
+    }
 }
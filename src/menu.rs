@@ -0,0 +1,220 @@
+//! Menus drawn over the playfield, driven by the keyboard or a gamepad.
+//!
+//! Items are picked with up and down. Items with choices cycle through them
+//! with left and right, plain items are activated with select.
+
+use engine::graphics::{Color, DrawMode, DrawPrimitives};
+use ggez::event::{Button, Keycode};
+use ggez::graphics::Point2;
+
+use palette::Palette;
+use playfield;
+
+const PANEL_WIDTH: f32 = 240.0;
+const ITEM_HEIGHT: f32 = 28.0;
+const PADDING: f32 = 20.0;
+// How much of the game still shows through behind the panel.
+const BACKDROP_ALPHA: f32 = 0.6;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MenuInput {
+    Up,
+    Down,
+    Left,
+    Right,
+    Select,
+    Back,
+}
+
+impl MenuInput {
+    pub fn from_keycode(keycode: Keycode) -> Option<MenuInput> {
+        match keycode {
+            Keycode::Up | Keycode::W => Some(MenuInput::Up),
+            Keycode::Down | Keycode::S => Some(MenuInput::Down),
+            Keycode::Left | Keycode::A => Some(MenuInput::Left),
+            Keycode::Right | Keycode::D => Some(MenuInput::Right),
+            Keycode::Return | Keycode::Space => Some(MenuInput::Select),
+            Keycode::Escape | Keycode::Backspace => Some(MenuInput::Back),
+            _ => None,
+        }
+    }
+
+    // Start pauses and resumes, like escape does.
+    pub fn from_button(button: Button) -> Option<MenuInput> {
+        match button {
+            Button::DPadUp => Some(MenuInput::Up),
+            Button::DPadDown => Some(MenuInput::Down),
+            Button::DPadLeft => Some(MenuInput::Left),
+            Button::DPadRight => Some(MenuInput::Right),
+            Button::A => Some(MenuInput::Select),
+            Button::B | Button::Back | Button::Start => Some(MenuInput::Back),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MenuEvent<A> {
+    Select(A),
+    // The item's choice moved to the given index.
+    Change(A, usize),
+    Back,
+}
+
+#[derive(Clone, Debug)]
+struct Item<A> {
+    label: String,
+    action: A,
+    choices: Vec<String>,
+    choice: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Menu<A> {
+    title: String,
+    // Lines of text between the title and the items.
+    notes: Vec<String>,
+    items: Vec<Item<A>>,
+    selected: usize,
+}
+
+impl<A> Menu<A>
+where
+    A: Copy,
+{
+    pub fn new(title: &str) -> Self {
+        Menu {
+            title: title.to_string(),
+            notes: Vec::new(),
+            items: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    pub fn note(mut self, text: &str) -> Self {
+        self.notes.push(text.to_string());
+        self
+    }
+
+    pub fn item(self, label: &str, action: A) -> Self {
+        self.choice(label, action, Vec::new(), 0)
+    }
+
+    // An item showing `choices[current]`, changed with left and right.
+    pub fn choice(mut self, label: &str, action: A, choices: Vec<String>, current: usize) -> Self {
+        self.items.push(Item {
+            label: label.to_string(),
+            action,
+            choices,
+            choice: current,
+        });
+        self
+    }
+
+    pub fn selected(&self) -> Option<A> {
+        self.items.get(self.selected).map(|item| item.action)
+    }
+
+    pub fn input(&mut self, input: MenuInput) -> Option<MenuEvent<A>> {
+        let count = self.items.len();
+        if count == 0 {
+            return match input {
+                MenuInput::Back => Some(MenuEvent::Back),
+                _ => None,
+            };
+        }
+        match input {
+            MenuInput::Up => self.selected = (self.selected + count - 1) % count,
+            MenuInput::Down => self.selected = (self.selected + 1) % count,
+            MenuInput::Left | MenuInput::Right => {
+                let item = &mut self.items[self.selected];
+                let choices = item.choices.len();
+                if choices > 0 {
+                    item.choice = match input {
+                        MenuInput::Left => (item.choice + choices - 1) % choices,
+                        _ => (item.choice + 1) % choices,
+                    };
+                    return Some(MenuEvent::Change(item.action, item.choice));
+                }
+            }
+            MenuInput::Select => {
+                let item = &self.items[self.selected];
+                if item.choices.is_empty() {
+                    return Some(MenuEvent::Select(item.action));
+                }
+            }
+            MenuInput::Back => return Some(MenuEvent::Back),
+        }
+        None
+    }
+
+    // A dimmed playfield with the menu on a panel in the middle.
+    pub fn render(&self, surface: &mut DrawPrimitives) {
+        let mut backdrop = Palette::Black.into_rgba();
+        backdrop[3] = BACKDROP_ALPHA;
+        surface.set_color(&backdrop);
+        surface.rectangle(
+            DrawMode::Fill,
+            Point2::new(0.0, 0.0),
+            playfield::width(),
+            playfield::height(),
+        );
+
+        let lines = 1 + self.notes.len() + self.items.len();
+        let height = ITEM_HEIGHT * lines as f32 + PADDING * 2.0;
+        let center = playfield::center();
+        let origin = Point2::new(center.x - PANEL_WIDTH / 2.0, center.y - height / 2.0);
+        surface.set_color(&Palette::Black);
+        surface.rounded_rectangle(DrawMode::Fill, origin, PANEL_WIDTH, height, 8.0);
+        surface.set_color(&Palette::Light(0.5));
+        surface.rounded_rectangle(DrawMode::Line(2.0), origin, PANEL_WIDTH, height, 8.0);
+
+        let left = origin.x + PADDING * 2.0;
+        let mut y = origin.y + PADDING;
+        surface.set_color(&Palette::Light(1.0));
+        surface.text(&self.title, Point2::new(left, y));
+        surface.set_color(&Palette::Light(0.5));
+        for note in self.notes.iter() {
+            y += ITEM_HEIGHT;
+            surface.text(note, Point2::new(left, y));
+        }
+        for (i, item) in self.items.iter().enumerate() {
+            y += ITEM_HEIGHT;
+            let label = match item.choices.get(item.choice) {
+                Some(choice) => format!("{}  < {} >", item.label, choice),
+                None => item.label.clone(),
+            };
+            if i == self.selected {
+                let marker = Point2::new(origin.x + PADDING, y + ITEM_HEIGHT / 4.0);
+                surface.set_color(&Palette::Player);
+                surface.polygon(
+                    DrawMode::Fill,
+                    &[
+                        marker,
+                        Point2::new(marker.x + 8.0, marker.y + 5.0),
+                        Point2::new(marker.x, marker.y + 10.0),
+                    ],
+                );
+            } else {
+                surface.set_color(&Palette::Light(0.0));
+            }
+            surface.text(&label, Point2::new(left, y));
+        }
+    }
+}
+
+#[test]
+fn menu_wraps_and_cycles_choices() {
+    let choices = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+    let mut menu = Menu::new("Test")
+        .item("First", 1)
+        .choice("Second", 2, choices, 0);
+    assert_eq!(menu.input(MenuInput::Up), None);
+    assert_eq!(menu.selected(), Some(2));
+    assert_eq!(menu.input(MenuInput::Left), Some(MenuEvent::Change(2, 2)));
+    assert_eq!(menu.input(MenuInput::Select), None);
+    menu.input(MenuInput::Down);
+    assert_eq!(menu.input(MenuInput::Right), None);
+    assert_eq!(menu.input(MenuInput::Select), Some(MenuEvent::Select(1)));
+    assert_eq!(menu.input(MenuInput::Back), Some(MenuEvent::Back));
+}
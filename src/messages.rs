@@ -6,7 +6,7 @@ pub enum MessageSender {
     God,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub enum Direction {
     Up,
     Down,
@@ -15,13 +15,18 @@ pub enum Direction {
     Point(Point2),
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub enum Message {
     Move(Direction, f32),
     Shoot,
     Start,
     Stop,
     Kill,
+    Damage(usize, f32),
+    Target(Point2),
+    CameraMoved(Point2),
+    // Sets the level of the weapon, how many shots go out at once.
+    Weapon(u32),
 }
 
 pub trait SendMessageTo<T> {
@@ -1,7 +1,45 @@
-use ggez::graphics::Color;
+//! Every color the game draws with goes through `Palette`, the actual RGB
+//! values come from the current `Theme`.
+//!
+//! Themes are text files, one color per line: a key followed by hex colors.
+//! `light` takes two, its dimmest and brightest shade.
+//!
+//! ```text
+//! # The original twins colors.
+//! name Default
+//! black #05000a
+//! light #b496c3 #f0d2ff
+//! player #bec8fa
+//! blink #ffffff
+//! debug #64f064
+//! ```
+
+use color;
+use engine::graphics::Color;
+use mekano_format::FormatError;
 use na;
-use std::convert::From;
 
+use std::cell::RefCell;
+use std::fs;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+// Contrast against the background gameplay colors need, WCAG's minimum for
+// graphics.
+const MIN_CONTRAST: f32 = 3.0;
+
+const BUILTIN: [&str; 3] = [
+    include_str!("../themes/default.theme"),
+    include_str!("../themes/colorblind.theme"),
+    include_str!("../themes/high_contrast.theme"),
+];
+
+thread_local! {
+    static THEME: RefCell<Theme> = RefCell::new(Theme::default());
+}
+
+#[derive(Clone, Copy, Debug)]
 pub enum Palette {
     Black,
     Light(f32),
@@ -10,18 +48,239 @@ pub enum Palette {
     DebugA,
 }
 
-impl From<Palette> for Color {
-    fn from(p: Palette) -> Color {
-        match p {
-            Palette::Black => Color::from_rgb(5, 0, 10),
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub black: [u8; 3],
+    pub light: ([u8; 3], [u8; 3]),
+    pub player: [u8; 3],
+    pub blink: [u8; 3],
+    pub debug: [u8; 3],
+}
+
+impl Theme {
+    pub fn builtin() -> Vec<Theme> {
+        BUILTIN
+            .iter()
+            .map(|text| Theme::parse(text).expect("built-in themes are valid"))
+            .collect()
+    }
+
+    // The built-in themes followed by any other `.theme` files found in `dir`.
+    pub fn available(dir: &Path) -> Vec<Theme> {
+        let mut themes = Theme::builtin();
+        let mut paths: Vec<_> = match fs::read_dir(dir) {
+            Ok(entries) => entries.filter_map(|e| e.ok()).map(|e| e.path()).collect(),
+            Err(_) => return themes,
+        };
+        paths.sort();
+        for path in paths {
+            if path.extension().map_or(true, |e| e != "theme") {
+                continue;
+            }
+            match Theme::load(&path) {
+                Ok(ref theme) if themes.contains(theme) => (),
+                Ok(theme) => themes.push(theme),
+                Err(e) => println!("Could not load theme {}: {}", path.display(), e),
+            }
+        }
+        themes
+    }
+
+    pub fn load(path: &Path) -> Result<Theme, FormatError> {
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+        Theme::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Theme, FormatError> {
+        let mut name = None;
+        let mut black = None;
+        let mut light = None;
+        let mut player = None;
+        let mut blink = None;
+        let mut debug = None;
+        for (i, raw) in text.lines().enumerate() {
+            let number = i + 1;
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = match line.find(char::is_whitespace) {
+                Some(at) => (&line[..at], line[at..].trim()),
+                None => (line, ""),
+            };
+            let colors = || {
+                value
+                    .split_whitespace()
+                    .map(|hex| {
+                        parse_hex(hex)
+                            .ok_or(FormatError::Syntax(number, format!("bad color {}", hex)))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            };
+            match key {
+                "name" => name = Some(value.to_string()),
+                "black" => black = Some(single(number, colors()?)?),
+                "player" => player = Some(single(number, colors()?)?),
+                "blink" => blink = Some(single(number, colors()?)?),
+                "debug" => debug = Some(single(number, colors()?)?),
+                "light" => {
+                    let shades = colors()?;
+                    if shades.len() != 2 {
+                        return Err(FormatError::Syntax(
+                            number,
+                            "light takes its dimmest and brightest shade".to_string(),
+                        ));
+                    }
+                    light = Some((shades[0], shades[1]));
+                }
+                _ => return Err(FormatError::Syntax(number, format!("unknown key {}", key))),
+            }
+        }
+        let missing = |key: &str| FormatError::Syntax(0, format!("missing {}", key));
+        Ok(Theme {
+            name: name.ok_or_else(|| missing("name"))?,
+            black: black.ok_or_else(|| missing("black"))?,
+            light: light.ok_or_else(|| missing("light"))?,
+            player: player.ok_or_else(|| missing("player"))?,
+            blink: blink.ok_or_else(|| missing("blink"))?,
+            debug: debug.ok_or_else(|| missing("debug"))?,
+        })
+    }
+
+    // Gameplay colors that are hard to tell apart from the background.
+    pub fn contrast_warnings(&self) -> Vec<String> {
+        let gameplay = [
+            ("dim light", self.light.0),
+            ("bright light", self.light.1),
+            ("player", self.player),
+            ("blink", self.blink),
+        ];
+        gameplay
+            .iter()
+            .filter_map(|&(name, color)| {
+                let ratio = contrast(self.black, color);
+                if ratio < MIN_CONTRAST {
+                    Some(format!(
+                        "{}: {} against black has a contrast of {:.2}, below {}",
+                        self.name, name, ratio, MIN_CONTRAST
+                    ))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn rgba(&self, palette: Palette) -> [f32; 4] {
+        match palette {
+            Palette::Black => rgb(self.black),
             Palette::Light(intensity) => {
                 let i = na::clamp(intensity, 0.0, 1.0);
-                let v = (i * 60.0) as u8;
-                Color::from_rgb(180 + v, 150 + v, 195 + v)
+                let (dim, bright) = self.light;
+                let mut shade = [0; 3];
+                for c in 0..3 {
+                    let range = bright[c] as f32 - dim[c] as f32;
+                    shade[c] = (dim[c] as f32 + (i * range).trunc()) as u8;
+                }
+                rgb(shade)
             }
-            Palette::Player => Color::from_rgb(190, 200, 250),
-            Palette::Blink(opacity) => Color::from_rgba(255, 255, 255, (255.0 * opacity) as u8),
-            Palette::DebugA => Color::from_rgb(100, 240, 100),
+            Palette::Player => rgb(self.player),
+            Palette::Blink(opacity) => rgba(self.blink, (255.0 * opacity) as u8),
+            Palette::DebugA => rgb(self.debug),
         }
     }
 }
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::parse(BUILTIN[0]).expect("the default theme is valid")
+    }
+}
+
+// Every `Palette` color is drawn from this theme from now on.
+pub fn set_theme(theme: Theme) {
+    for warning in theme.contrast_warnings() {
+        println!("Warning: {}", warning);
+    }
+    THEME.with(|current| *current.borrow_mut() = theme);
+}
+
+pub fn theme() -> Theme {
+    THEME.with(|current| current.borrow().clone())
+}
+
+fn single(number: usize, colors: Vec<[u8; 3]>) -> Result<[u8; 3], FormatError> {
+    if colors.len() == 1 {
+        Ok(colors[0])
+    } else {
+        Err(FormatError::Syntax(
+            number,
+            "expected one color".to_string(),
+        ))
+    }
+}
+
+// `#rrggbb`, anything else, even non-ASCII, is rejected before slicing.
+fn parse_hex(hex: &str) -> Option<[u8; 3]> {
+    if !hex.starts_with('#') || hex.len() != 7 {
+        return None;
+    }
+    if !hex[1..].chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |at: usize| u8::from_str_radix(&hex[at..at + 2], 16).ok();
+    Some([channel(1)?, channel(3)?, channel(5)?])
+}
+
+// WCAG contrast ratio, from 1 for equal colors up to 21 for black on white.
+fn contrast(a: [u8; 3], b: [u8; 3]) -> f32 {
+    let (la, lb) = (luminance(a), luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+fn luminance(color: [u8; 3]) -> f32 {
+    let linear = |c: u8| color::to_linear(c as f32 / 255.0);
+    0.2126 * linear(color[0]) + 0.7152 * linear(color[1]) + 0.0722 * linear(color[2])
+}
+
+fn rgb(color: [u8; 3]) -> [f32; 4] {
+    rgba(color, 255)
+}
+
+fn rgba(color: [u8; 3], a: u8) -> [f32; 4] {
+    [
+        color[0] as f32 / 255.0,
+        color[1] as f32 / 255.0,
+        color[2] as f32 / 255.0,
+        a as f32 / 255.0,
+    ]
+}
+
+impl Color for Palette {
+    fn into_rgba(&self) -> [f32; 4] {
+        THEME.with(|theme| theme.borrow().rgba(*self))
+    }
+}
+
+#[test]
+fn builtin_themes_are_readable() {
+    let themes = Theme::builtin();
+    assert_eq!(themes[0], Theme::default());
+    assert_eq!(Palette::Light(0.5).into_rgba(), rgb([210, 180, 225]));
+    for theme in themes.iter() {
+        assert!(theme.contrast_warnings().is_empty(), "{}", theme.name);
+    }
+}
+
+#[test]
+fn dim_themes_warn_about_contrast() {
+    let mut theme = Theme::default();
+    theme.player = [20, 10, 30];
+    assert_eq!(theme.contrast_warnings().len(), 1);
+    assert!(Theme::parse("name Broken\nblack #000000").is_err());
+    assert_eq!(parse_hex("#1é234"), None);
+    assert_eq!(parse_hex("#+1+2+3"), None);
+    assert_eq!(parse_hex("#2a0F80"), Some([42, 15, 128]));
+}
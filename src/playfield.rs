@@ -0,0 +1,100 @@
+//! The logical playfield everything is laid out in, `W_WIDTH` by `W_HEIGHT`
+//! units. Windows of any size show it scaled up and letterboxed.
+
+use engine::graphics::Transform;
+use ggez::graphics::Point2;
+
+use {W_HEIGHT, W_WIDTH};
+
+pub fn width() -> f32 {
+    W_WIDTH as f32
+}
+
+pub fn height() -> f32 {
+    W_HEIGHT as f32
+}
+
+pub fn center() -> Point2 {
+    at(0.5, 0.5)
+}
+
+// A point given as fractions of the playfield, (0, 0) top left and (1, 1)
+// bottom right. Values outside [0, 1] are off screen.
+pub fn at(x: f32, y: f32) -> Point2 {
+    Point2::new(x * width(), y * height())
+}
+
+/// Maps the playfield onto a window of any size, keeping its aspect ratio.
+#[derive(Debug, Clone)]
+pub struct Viewport {
+    width: f32,
+    height: f32,
+}
+
+impl Viewport {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width: width as f32,
+            height: height as f32,
+        }
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.width = width as f32;
+        self.height = height as f32;
+    }
+
+    pub fn scale(&self) -> f32 {
+        (self.width / width()).min(self.height / height())
+    }
+
+    // Window position of the playfield's top left corner.
+    pub fn offset(&self) -> Point2 {
+        let scale = self.scale();
+        Point2::new(
+            (self.width - width() * scale) / 2.0,
+            (self.height - height() * scale) / 2.0,
+        )
+    }
+
+    pub fn transform(&self) -> Transform {
+        let offset = self.offset();
+        let scale = self.scale();
+        Transform::translate(offset.x, offset.y).then(&Transform::scale(scale, scale))
+    }
+
+    // Bars covering the window around the playfield, as (origin, width, height).
+    pub fn letterbox(&self) -> Vec<(Point2, f32, f32)> {
+        let offset = self.offset();
+        let mut bars = Vec::new();
+        if offset.x > 0.0 {
+            bars.push((Point2::new(0.0, 0.0), offset.x, self.height));
+            bars.push((
+                Point2::new(self.width - offset.x, 0.0),
+                offset.x,
+                self.height,
+            ));
+        }
+        if offset.y > 0.0 {
+            bars.push((Point2::new(0.0, 0.0), self.width, offset.y));
+            bars.push((
+                Point2::new(0.0, self.height - offset.y),
+                self.width,
+                offset.y,
+            ));
+        }
+        bars
+    }
+}
+
+#[test]
+fn viewport_letterboxes_wide_windows() {
+    let viewport = Viewport::new(W_WIDTH * 4, W_HEIGHT * 2);
+    assert_eq!(viewport.scale(), 2.0);
+    assert_eq!(viewport.offset(), Point2::new(width(), 0.0));
+    assert_eq!(
+        viewport.transform().apply(at(1.0, 1.0)),
+        Point2::new(width() * 3.0, height() * 2.0)
+    );
+    assert_eq!(viewport.letterbox().len(), 2);
+}
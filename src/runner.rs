@@ -0,0 +1,167 @@
+//! Runs anything built on `engine::logical` and `engine::graphics`, in a ggez
+//! window or inside a terminal.
+
+use ggez::event::{Button, Keycode, Mod};
+use ggez::graphics::Rect;
+use ggez::{conf, event, graphics, Context, GameResult};
+
+use engine::graphics::{Draw, DrawMode, DrawPrimitives, Graphics};
+use engine::logical::{Input, Update};
+use palette::Palette;
+use playfield::Viewport;
+use screen::Screen;
+use svg::SvgDocument;
+use terminal::{Key, Keyboard, Terminal};
+
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use {W_HEIGHT, W_WIDTH};
+
+pub struct Main<W> {
+    world: W,
+    viewport: Viewport,
+    fullscreen: bool,
+    frame: u64,
+}
+
+impl<W> Main<W>
+where
+    W: Draw<SvgDocument>,
+{
+    pub fn new(world: W) -> Self {
+        Self {
+            world,
+            viewport: Viewport::new(W_WIDTH, W_HEIGHT),
+            fullscreen: false,
+            frame: 0,
+        }
+    }
+
+    // Writes the current frame as an SVG still in the working directory.
+    fn export_frame(&mut self) {
+        let mut document = SvgDocument::new(W_WIDTH, W_HEIGHT);
+        self.world.draw(&mut document);
+        let path = format!("twins_{:06}.svg", self.frame);
+        match document.save(Path::new(&path)) {
+            Ok(()) => println!("Saved frame to {}", path),
+            Err(e) => println!("Could not save frame to {}: {}", path, e),
+        }
+    }
+}
+
+impl<W> event::EventHandler for Main<W>
+where
+    for<'a> W:
+        Update<Context> + Draw<Screen<'a>> + Draw<SvgDocument> + Input<Keycode> + Input<Button>,
+{
+    fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
+        self.world.update(ctx);
+        self.frame += 1;
+        if self.world.finished() {
+            ctx.quit()?;
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
+        let mut screen = Screen::new(ctx);
+        screen.push_transform(self.viewport.transform());
+        self.world.draw(&mut screen);
+        screen.pop_transform();
+        screen.set_color(&Palette::Black);
+        for (origin, width, height) in self.viewport.letterbox() {
+            screen.rectangle(DrawMode::Fill, origin, width, height);
+        }
+        screen.present();
+        Ok(())
+    }
+
+    fn key_down_event(&mut self, ctx: &mut Context, keycode: Keycode, _keymod: Mod, repeat: bool) {
+        if repeat {
+            return;
+        }
+        match keycode {
+            Keycode::F12 => self.export_frame(),
+            Keycode::F11 => {
+                self.fullscreen = !self.fullscreen;
+                graphics::set_fullscreen(ctx, self.fullscreen).unwrap();
+                let (width, height) = graphics::get_drawable_size(ctx);
+                self.resize_event(ctx, width, height);
+            }
+            _ => self.world.key_down(keycode),
+        }
+    }
+
+    fn key_up_event(&mut self, _ctx: &mut Context, keycode: Keycode, _keymod: Mod, repeat: bool) {
+        if repeat {
+            return;
+        }
+        self.world.key_up(keycode);
+    }
+
+    fn controller_button_down_event(
+        &mut self,
+        _ctx: &mut Context,
+        button: Button,
+        _instance_id: i32,
+    ) {
+        self.world.key_down(button);
+    }
+
+    fn controller_button_up_event(
+        &mut self,
+        _ctx: &mut Context,
+        button: Button,
+        _instance_id: i32,
+    ) {
+        self.world.key_up(button);
+    }
+
+    fn resize_event(&mut self, ctx: &mut Context, width: u32, height: u32) {
+        self.viewport.resize(width, height);
+        let window = Rect::new(0.0, 0.0, width as f32, height as f32);
+        graphics::set_screen_coordinates(ctx, window).unwrap();
+    }
+}
+
+/// Opens a resizable window showing the playfield and runs `world` in it.
+pub fn run<W>(title: &str, world: W)
+where
+    for<'a> W:
+        Update<Context> + Draw<Screen<'a>> + Draw<SvgDocument> + Input<Keycode> + Input<Button>,
+{
+    let mut c = conf::Conf::new();
+    c.window_setup.title = title.to_string();
+    c.window_setup.resizable = true;
+    c.window_mode.width = W_WIDTH;
+    c.window_mode.height = W_HEIGHT;
+    let ctx = &mut Context::load_from_conf("twins", "jbat1jumper", c).unwrap();
+    event::run(ctx, &mut Main::new(world)).unwrap();
+}
+
+/// Runs `world` inside the current terminal until escape is pressed or it
+/// finishes. The terminal only reports presses, so every key is released
+/// right away.
+pub fn run_in_terminal<W>(mut world: W)
+where
+    W: Update<Terminal> + Draw<Terminal> + Input<Key>,
+{
+    let keyboard = Keyboard::open().unwrap();
+    let mut terminal = Terminal::fit(W_WIDTH as f32, W_HEIGHT as f32);
+    loop {
+        terminal.keys = keyboard.poll();
+        if terminal.keys.contains(&Key::Escape) || world.finished() {
+            break;
+        }
+        for key in terminal.keys.iter() {
+            world.key_down(*key);
+            world.key_up(*key);
+        }
+        world.update(&mut terminal);
+        world.draw(&mut terminal);
+        terminal.present();
+        thread::sleep(Duration::from_millis(33));
+    }
+}
@@ -0,0 +1,160 @@
+use engine::graphics::{Color, DrawMode, DrawPrimitives, Graphics, MappedEllipse, TransformStack};
+use ggez;
+use ggez::graphics::Font;
+use nalgebra::Point2;
+
+pub struct Screen<'a> {
+    pub ctx: &'a mut ggez::Context,
+    pub precision: f32,
+    transforms: TransformStack,
+    // Text is skipped when the font couldn't be loaded.
+    font: Option<Font>,
+}
+
+impl<'a> Screen<'a> {
+    pub fn new(ctx: &'a mut ggez::Context) -> Self {
+        let font = match Font::default_font() {
+            Ok(font) => Some(font),
+            Err(e) => {
+                warn!("Could not load the default font: {}", e);
+                None
+            }
+        };
+        Self {
+            ctx,
+            precision: 0.5,
+            transforms: TransformStack::new(),
+            font,
+        }
+    }
+}
+
+// A failed draw call loses one primitive for one frame, not worth a panic.
+fn report(result: ggez::GameResult<()>) {
+    if let Err(e) = result {
+        warn!("Drawing failed: {}", e);
+    }
+}
+
+fn convert_color(color: &Color) -> ggez::graphics::Color {
+    ggez::graphics::Color::from(color.into_rgba())
+}
+
+fn convert_point(point: Point2<f32>) -> ggez::nalgebra::Point2<f32> {
+    ggez::nalgebra::Point2::new(point.x, point.y)
+}
+
+impl<'a> Graphics for Screen<'a> {
+    fn clear(&mut self, color: &Color) {
+        ggez::graphics::set_background_color(&mut self.ctx, convert_color(color));
+        ggez::graphics::clear(&mut self.ctx);
+    }
+    fn present(&mut self) {
+        ggez::graphics::present(&mut self.ctx);
+    }
+}
+
+fn convert_draw_mode(dm: DrawMode) -> ggez::graphics::DrawMode {
+    match dm {
+        DrawMode::Line(w) => ggez::graphics::DrawMode::Line(w),
+        DrawMode::Fill => ggez::graphics::DrawMode::Fill,
+    }
+}
+
+impl<'a> DrawPrimitives for Screen<'a> {
+    fn transform_stack(&mut self) -> &mut TransformStack {
+        &mut self.transforms
+    }
+    fn set_color(&mut self, color: &Color) {
+        report(ggez::graphics::set_color(
+            &mut self.ctx,
+            convert_color(color),
+        ));
+    }
+    fn circle(&mut self, dm: DrawMode, origin: Point2<f32>, radius: f32) {
+        self.ellipse(dm, origin, radius, radius);
+    }
+    fn ellipse(&mut self, dm: DrawMode, origin: Point2<f32>, width: f32, height: f32) {
+        let t = self.transforms.current();
+        match t.ellipse(origin, width, height) {
+            MappedEllipse::Ellipse(origin, width, height) if width == height => {
+                report(ggez::graphics::circle(
+                    &mut self.ctx,
+                    convert_draw_mode(t.mode(dm)),
+                    convert_point(origin),
+                    width,
+                    self.precision,
+                ));
+            }
+            MappedEllipse::Ellipse(origin, width, height) => {
+                report(ggez::graphics::ellipse(
+                    &mut self.ctx,
+                    convert_draw_mode(t.mode(dm)),
+                    convert_point(origin),
+                    width,
+                    height,
+                    self.precision,
+                ));
+            }
+            MappedEllipse::Polygon(points) => {
+                let points: Vec<_> = points.iter().map(|p| convert_point(*p)).collect();
+                report(ggez::graphics::polygon(
+                    &mut self.ctx,
+                    convert_draw_mode(t.mode(dm)),
+                    &points,
+                ));
+            }
+        }
+    }
+    fn line(&mut self, origin: Point2<f32>, target: Point2<f32>, width: f32) {
+        let t = self.transforms.current();
+        report(ggez::graphics::line(
+            &mut self.ctx,
+            &[
+                convert_point(t.apply(origin)),
+                convert_point(t.apply(target)),
+            ],
+            t.length(width),
+        ));
+    }
+    fn text(&mut self, text: &str, origin: Point2<f32>) {
+        let origin = self.transforms.current().apply(origin);
+        let font = match self.font {
+            Some(ref font) => font,
+            None => return,
+        };
+        match ggez::graphics::Text::new(&mut self.ctx, text, font) {
+            Ok(t) => report(ggez::graphics::draw(
+                &mut self.ctx,
+                &t,
+                convert_point(origin),
+                0.0,
+            )),
+            Err(e) => warn!("Could not lay out text: {}", e),
+        }
+    }
+    fn polygon(&mut self, dm: DrawMode, points: &[Point2<f32>]) {
+        if points.len() < 3 {
+            return;
+        }
+        let t = self.transforms.current();
+        let points: Vec<_> = points.iter().map(|p| convert_point(t.apply(*p))).collect();
+        report(ggez::graphics::polygon(
+            &mut self.ctx,
+            convert_draw_mode(t.mode(dm)),
+            &points,
+        ));
+    }
+    fn polyline(&mut self, points: &[Point2<f32>], width: f32) {
+        if points.len() < 2 {
+            return;
+        }
+        let t = self.transforms.current();
+        let points: Vec<_> = points.iter().map(|p| convert_point(t.apply(*p))).collect();
+        report(ggez::graphics::line(
+            &mut self.ctx,
+            &points,
+            t.length(width),
+        ));
+    }
+}
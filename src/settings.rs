@@ -0,0 +1,181 @@
+//! Choices remembered between launches, kept as `key value` lines in the
+//! user's config directory.
+
+use mekano_format::FormatError;
+
+use std::env;
+use std::fs;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+// Highest volume, there are as many steps below it down to silence.
+pub const MAX_VOLUME: u32 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bindings {
+    Wasd,
+    Arrows,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    // Scales the time between enemies.
+    pub fn enemy_interval(self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.5,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 0.6,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    // Set once the intro is skipped, later launches go straight to play.
+    pub skip_intro: bool,
+    // Kept in the file, but left out of the options until there is audio.
+    pub volume: u32,
+    pub bindings: Bindings,
+    // Name of the palette theme.
+    pub theme: String,
+    pub difficulty: Difficulty,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            skip_intro: false,
+            volume: MAX_VOLUME,
+            bindings: Bindings::Wasd,
+            theme: "Default".to_string(),
+            difficulty: Difficulty::Normal,
+        }
+    }
+}
+
+impl Settings {
+    pub fn path() -> PathBuf {
+        let config = match (env::var_os("XDG_CONFIG_HOME"), env::var_os("HOME")) {
+            (Some(dir), _) => PathBuf::from(dir),
+            (None, Some(home)) => PathBuf::from(home).join(".config"),
+            (None, None) => PathBuf::from("."),
+        };
+        config.join("twins").join("settings")
+    }
+
+    // Missing or broken settings fall back to the defaults.
+    pub fn load() -> Settings {
+        let path = Settings::path();
+        let mut text = String::new();
+        if File::open(&path)
+            .and_then(|mut f| f.read_to_string(&mut text))
+            .is_err()
+        {
+            return Settings::default();
+        }
+        Settings::parse(&text).unwrap_or_else(|e| {
+            println!("Ignoring settings in {}: {}", path.display(), e);
+            Settings::default()
+        })
+    }
+
+    pub fn save(&self) {
+        let path = Settings::path();
+        let written = path
+            .parent()
+            .map_or(Ok(()), |dir| fs::create_dir_all(dir))
+            .and_then(|_| File::create(&path))
+            .and_then(|mut f| f.write_all(self.to_text().as_bytes()));
+        if let Err(e) = written {
+            println!("Could not save settings to {}: {}", path.display(), e);
+        }
+    }
+
+    // Unknown keys are skipped, they may come from a newer version.
+    pub fn parse(text: &str) -> Result<Settings, FormatError> {
+        let mut settings = Settings::default();
+        for (i, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let bad = |what: &str, value: &str| {
+                FormatError::Syntax(i + 1, format!("bad {} {}", what, value))
+            };
+            match (words.next(), words.next()) {
+                (Some("skip_intro"), Some(value)) => {
+                    settings.skip_intro = value.parse().map_err(|_| bad("flag", value))?;
+                }
+                (Some("volume"), Some(value)) => {
+                    settings.volume = value
+                        .parse()
+                        .ok()
+                        .filter(|&v| v <= MAX_VOLUME)
+                        .ok_or_else(|| bad("volume", value))?;
+                }
+                (Some("bindings"), Some(value)) => {
+                    settings.bindings = match value {
+                        "wasd" => Bindings::Wasd,
+                        "arrows" => Bindings::Arrows,
+                        _ => return Err(bad("bindings", value)),
+                    };
+                }
+                (Some("theme"), Some(_)) => {
+                    settings.theme = line["theme".len()..].trim().to_string();
+                }
+                (Some("difficulty"), Some(value)) => {
+                    settings.difficulty = match value {
+                        "easy" => Difficulty::Easy,
+                        "normal" => Difficulty::Normal,
+                        "hard" => Difficulty::Hard,
+                        _ => return Err(bad("difficulty", value)),
+                    };
+                }
+                _ => (),
+            }
+        }
+        Ok(settings)
+    }
+
+    pub fn to_text(&self) -> String {
+        let bindings = match self.bindings {
+            Bindings::Wasd => "wasd",
+            Bindings::Arrows => "arrows",
+        };
+        let difficulty = match self.difficulty {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        };
+        format!(
+            "skip_intro {}\nvolume {}\nbindings {}\ntheme {}\ndifficulty {}\n",
+            self.skip_intro, self.volume, bindings, self.theme, difficulty
+        )
+    }
+}
+
+#[test]
+fn settings_round_trip() {
+    let settings = Settings {
+        skip_intro: true,
+        volume: 3,
+        bindings: Bindings::Arrows,
+        theme: "High contrast".to_string(),
+        difficulty: Difficulty::Hard,
+    };
+    assert_eq!(Settings::parse(&settings.to_text()).unwrap(), settings);
+    assert_eq!(
+        Settings::parse("# old\nlanguage en\n").unwrap(),
+        Settings::default()
+    );
+    assert!(Settings::parse("skip_intro maybe").is_err());
+    assert!(Settings::parse("volume 11").is_err());
+}
@@ -0,0 +1,332 @@
+use engine::graphics::{
+    polygon_contains, segment_distance, Color, DrawMode, DrawPrimitives, Graphics, MappedEllipse,
+    TransformStack,
+};
+use image;
+use image::{Rgba, RgbaImage};
+use nalgebra::Point2;
+
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Distance, in pixels, over which shape edges fade out.
+const EDGE: f32 = 1.0;
+
+pub struct Canvas {
+    image: RgbaImage,
+    color: [f32; 4],
+    transforms: TransformStack,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            image: RgbaImage::new(width, height),
+            color: [1.0, 1.0, 1.0, 1.0],
+            transforms: TransformStack::new(),
+        }
+    }
+
+    pub fn image(&self) -> &RgbaImage {
+        &self.image
+    }
+
+    pub fn save_png(&self, path: &Path) -> io::Result<()> {
+        self.image.save(path)
+    }
+
+    // Compares the canvas with `golden/<name>.png`. Running with TWINS_BLESS
+    // set writes the current canvas as the new golden, a missing one fails.
+    pub fn assert_golden(&self, name: &str) {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("golden");
+        let path = dir.join(format!("{}.png", name));
+        if env::var("TWINS_BLESS").is_ok() {
+            ::std::fs::create_dir_all(&dir).unwrap();
+            self.save_png(&path).unwrap();
+            return;
+        }
+        if !path.exists() {
+            panic!(
+                "missing golden {}, run with TWINS_BLESS set to create it",
+                path.display()
+            );
+        }
+        let golden = image::open(&path).unwrap().to_rgba();
+        let matches = golden.dimensions() == self.image.dimensions()
+            && golden.pixels().zip(self.image.pixels()).all(|(a, b)| {
+                a.data
+                    .iter()
+                    .zip(b.data.iter())
+                    .all(|(x, y)| (*x as i32 - *y as i32).abs() <= 2)
+            });
+        if !matches {
+            let actual = dir.join(format!("{}.actual.png", name));
+            self.save_png(&actual).unwrap();
+            panic!("{} differs from {}", actual.display(), path.display());
+        }
+    }
+
+    fn blend(&mut self, x: u32, y: u32, coverage: f32) {
+        let alpha = self.color[3] * coverage;
+        if alpha <= 0.0 {
+            return;
+        }
+        let pixel = self.image.get_pixel_mut(x, y);
+        let dst_alpha = pixel.data[3] as f32 / 255.0;
+        let out_alpha = alpha + dst_alpha * (1.0 - alpha);
+        for c in 0..3 {
+            let dst = pixel.data[c] as f32 / 255.0;
+            let out = (self.color[c] * alpha + dst * dst_alpha * (1.0 - alpha)) / out_alpha;
+            pixel.data[c] = to_byte(out);
+        }
+        pixel.data[3] = to_byte(out_alpha);
+    }
+
+    // Covers the pixels between `min` and `max` using a signed distance
+    // function, negative inside the shape.
+    fn fill<F>(&mut self, min: Point2<f32>, max: Point2<f32>, distance: F)
+    where
+        F: Fn(f32, f32) -> f32,
+    {
+        let (width, height) = self.image.dimensions();
+        let x0 = (min.x - EDGE).floor().max(0.0) as u32;
+        let y0 = (min.y - EDGE).floor().max(0.0) as u32;
+        let x1 = ((max.x + EDGE).ceil().max(0.0) as u32).min(width);
+        let y1 = ((max.y + EDGE).ceil().max(0.0) as u32).min(height);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let d = distance(x as f32 + 0.5, y as f32 + 0.5);
+                let coverage = (0.5 - d / EDGE).max(0.0).min(1.0);
+                if coverage > 0.0 {
+                    self.blend(x, y, coverage);
+                }
+            }
+        }
+    }
+}
+
+fn to_byte(value: f32) -> u8 {
+    (value.max(0.0).min(1.0) * 255.0).round() as u8
+}
+
+fn outline(mode: DrawMode, distance: f32) -> f32 {
+    match mode {
+        DrawMode::Fill => distance,
+        DrawMode::Line(width) => distance.abs() - width / 2.0,
+    }
+}
+
+fn bounds(points: &[Point2<f32>], reach: f32) -> (Point2<f32>, Point2<f32>) {
+    let mut min = Point2::new(::std::f32::MAX, ::std::f32::MAX);
+    let mut max = Point2::new(::std::f32::MIN, ::std::f32::MIN);
+    for p in points.iter() {
+        min = Point2::new(min.x.min(p.x), min.y.min(p.y));
+        max = Point2::new(max.x.max(p.x), max.y.max(p.y));
+    }
+    (
+        Point2::new(min.x - reach, min.y - reach),
+        Point2::new(max.x + reach, max.y + reach),
+    )
+}
+
+fn stroke(mode: DrawMode) -> f32 {
+    match mode {
+        DrawMode::Fill => 0.0,
+        DrawMode::Line(width) => width / 2.0,
+    }
+}
+
+impl Graphics for Canvas {
+    fn clear(&mut self, color: &Color) {
+        let c = color.into_rgba();
+        let pixel = Rgba([to_byte(c[0]), to_byte(c[1]), to_byte(c[2]), to_byte(c[3])]);
+        for p in self.image.pixels_mut() {
+            *p = pixel;
+        }
+    }
+    fn present(&mut self) {}
+}
+
+// Primitives in pixel coordinates, after the transform was applied.
+impl Canvas {
+    fn draw_circle(&mut self, mode: DrawMode, origin: Point2<f32>, radius: f32) {
+        let reach = radius + stroke(mode);
+        self.fill(
+            Point2::new(origin.x - reach, origin.y - reach),
+            Point2::new(origin.x + reach, origin.y + reach),
+            |x, y| {
+                let d = ((x - origin.x).powi(2) + (y - origin.y).powi(2)).sqrt();
+                outline(mode, d - radius)
+            },
+        );
+    }
+    fn draw_ellipse(&mut self, mode: DrawMode, origin: Point2<f32>, width: f32, height: f32) {
+        let reach_x = width + stroke(mode);
+        let reach_y = height + stroke(mode);
+        self.fill(
+            Point2::new(origin.x - reach_x, origin.y - reach_y),
+            Point2::new(origin.x + reach_x, origin.y + reach_y),
+            |x, y| {
+                let (x, y) = (x - origin.x, y - origin.y);
+                let f = ((x / width).powi(2) + (y / height).powi(2)).sqrt();
+                let g = ((x / width.powi(2)).powi(2) + (y / height.powi(2)).powi(2)).sqrt();
+                let d = match g > 0.0 {
+                    true => (f - 1.0) * f / g,
+                    false => -width.min(height),
+                };
+                outline(mode, d)
+            },
+        );
+    }
+    fn draw_line(&mut self, origin: Point2<f32>, target: Point2<f32>, width: f32) {
+        let (dx, dy) = (target.x - origin.x, target.y - origin.y);
+        let length = (dx * dx + dy * dy).sqrt();
+        if length <= 0.0 {
+            return;
+        }
+        let reach = width / 2.0;
+        self.fill(
+            Point2::new(
+                origin.x.min(target.x) - reach,
+                origin.y.min(target.y) - reach,
+            ),
+            Point2::new(
+                origin.x.max(target.x) + reach,
+                origin.y.max(target.y) + reach,
+            ),
+            |x, y| {
+                let (px, py) = (x - origin.x, y - origin.y);
+                let along = (px * dx + py * dy) / length;
+                let across = (px * dy - py * dx) / length;
+                (across.abs() - reach).max((along - length / 2.0).abs() - length / 2.0)
+            },
+        );
+    }
+    fn draw_polygon(&mut self, mode: DrawMode, points: &[Point2<f32>]) {
+        if points.len() < 3 {
+            return;
+        }
+        let (min, max) = bounds(points, stroke(mode));
+        self.fill(min, max, |x, y| {
+            let p = Point2::new(x, y);
+            let mut d = ::std::f32::MAX;
+            let mut j = points.len() - 1;
+            for i in 0..points.len() {
+                d = d.min(segment_distance(p, points[j], points[i]));
+                j = i;
+            }
+            match polygon_contains(p, points) {
+                true => outline(mode, -d),
+                false => outline(mode, d),
+            }
+        });
+    }
+    // A single distance field, so overlapping segments don't blend twice.
+    fn draw_polyline(&mut self, points: &[Point2<f32>], width: f32) {
+        if points.len() < 2 {
+            return;
+        }
+        let (min, max) = bounds(points, width / 2.0);
+        self.fill(min, max, |x, y| {
+            let d = points
+                .windows(2)
+                .map(|s| segment_distance(Point2::new(x, y), s[0], s[1]))
+                .fold(::std::f32::MAX, f32::min);
+            d - width / 2.0
+        });
+    }
+}
+
+impl DrawPrimitives for Canvas {
+    fn transform_stack(&mut self) -> &mut TransformStack {
+        &mut self.transforms
+    }
+    fn set_color(&mut self, color: &Color) {
+        self.color = color.into_rgba();
+    }
+    fn circle(&mut self, mode: DrawMode, origin: Point2<f32>, radius: f32) {
+        self.ellipse(mode, origin, radius, radius);
+    }
+    fn ellipse(&mut self, mode: DrawMode, origin: Point2<f32>, width: f32, height: f32) {
+        let t = self.transforms.current();
+        match t.ellipse(origin, width, height) {
+            MappedEllipse::Ellipse(origin, width, height) => match width == height {
+                true => self.draw_circle(t.mode(mode), origin, width),
+                false => self.draw_ellipse(t.mode(mode), origin, width, height),
+            },
+            MappedEllipse::Polygon(points) => self.draw_polygon(t.mode(mode), &points),
+        }
+    }
+    fn line(&mut self, origin: Point2<f32>, target: Point2<f32>, width: f32) {
+        let t = self.transforms.current();
+        self.draw_line(t.apply(origin), t.apply(target), t.length(width));
+    }
+    // There's no font rasterizer here, text is left out of the frame.
+    fn text(&mut self, _text: &str, _origin: Point2<f32>) {}
+    fn polygon(&mut self, mode: DrawMode, points: &[Point2<f32>]) {
+        let t = self.transforms.current();
+        self.draw_polygon(t.mode(mode), &t.apply_all(points));
+    }
+    fn polyline(&mut self, points: &[Point2<f32>], width: f32) {
+        let t = self.transforms.current();
+        self.draw_polyline(&t.apply_all(points), t.length(width));
+    }
+}
+
+#[test]
+fn canvas_blends_antialiased_circle() {
+    let mut canvas = Canvas::new(20, 20);
+    canvas.clear(&[0.0, 0.0, 0.0, 1.0]);
+    canvas.set_color(&[1.0, 1.0, 1.0, 0.5]);
+    canvas.circle(DrawMode::Fill, Point2::new(10.0, 10.0), 5.5);
+    let center = canvas.image().get_pixel(10, 10).data;
+    let edge = canvas.image().get_pixel(15, 10).data;
+    let outside = canvas.image().get_pixel(18, 10).data;
+    assert_eq!(center, [128, 128, 128, 255]);
+    assert!(edge[0] > 0 && edge[0] < 128);
+    assert_eq!(outside, [0, 0, 0, 255]);
+}
+
+#[test]
+fn canvas_fills_concave_polygon() {
+    let mut canvas = Canvas::new(20, 20);
+    canvas.clear(&[0.0, 0.0, 0.0, 1.0]);
+    canvas.set_color(&[1.0, 1.0, 1.0, 1.0]);
+    // a "U" shape, the notch between its arms stays empty
+    let u = [
+        Point2::new(2.0, 2.0),
+        Point2::new(8.0, 2.0),
+        Point2::new(8.0, 12.0),
+        Point2::new(12.0, 12.0),
+        Point2::new(12.0, 2.0),
+        Point2::new(18.0, 2.0),
+        Point2::new(18.0, 18.0),
+        Point2::new(2.0, 18.0),
+    ];
+    canvas.polygon(DrawMode::Fill, &u);
+    assert_eq!(canvas.image().get_pixel(4, 4).data, [255, 255, 255, 255]);
+    assert_eq!(canvas.image().get_pixel(10, 6).data, [0, 0, 0, 255]);
+    assert_eq!(canvas.image().get_pixel(10, 15).data, [255, 255, 255, 255]);
+
+    assert!(!polygon_contains(Point2::new(4.0, 4.0), &[]));
+    assert!(!polygon_contains(Point2::new(4.0, 4.0), &u[..2]));
+}
+
+#[test]
+fn canvas_applies_transform_stack() {
+    use engine::graphics::Transform;
+    let mut canvas = Canvas::new(20, 20);
+    canvas.clear(&[0.0, 0.0, 0.0, 1.0]);
+    canvas.set_color(&[1.0, 1.0, 1.0, 1.0]);
+    canvas.push_transform(Transform::translate(10.0, 10.0));
+    canvas.push_transform(Transform::scale(2.0, 2.0));
+    canvas.rectangle(DrawMode::Fill, Point2::new(0.0, 0.0), 3.0, 3.0);
+    canvas.pop_transform();
+    canvas.circle(DrawMode::Fill, Point2::new(-6.0, -6.0), 2.0);
+    canvas.pop_transform();
+    assert_eq!(canvas.image().get_pixel(15, 15).data, [255, 255, 255, 255]);
+    assert_eq!(canvas.image().get_pixel(17, 17).data, [0, 0, 0, 255]);
+    assert_eq!(canvas.image().get_pixel(4, 4).data, [255, 255, 255, 255]);
+    assert_eq!(canvas.image().get_pixel(9, 9).data, [0, 0, 0, 255]);
+}
@@ -1,176 +1,56 @@
-use entities::blink::Blink;
-use entities::intro::{MegaRay, MotherIntro, TwinIntro};
-use entities::stars::Stars;
-use entities::EntityId;
-use ggez::graphics::Point2;
-use messages::{Direction, Message, SendMessageTo};
+use cutscene::Timeline;
+use states::play::PlayState;
+use states::{State, Transition};
 use Game;
 
-const INTRO_SPEED: f32 = 5.0;
-
 #[derive(Debug)]
-pub struct IntroData {
-    time_left: f32,
-    mother: Option<EntityId>,
-    ray: Option<EntityId>,
-    twin1: Option<EntityId>,
-    twin2: Option<EntityId>,
+pub struct IntroState {
+    timeline: Timeline,
 }
 
-impl IntroData {
-    fn new() -> IntroData {
-        IntroData {
-            time_left: 0.0,
-            mother: None,
-            ray: None,
-            twin1: None,
-            twin2: None,
+impl IntroState {
+    pub fn new() -> Self {
+        IntroState {
+            timeline: Timeline::intro(),
         }
     }
-    fn wait(&self, time: f32) -> IntroData {
-        IntroData {
-            time_left: time,
-            ..*self
-        }
+}
+
+impl State for IntroState {
+    fn on_exit(&mut self, game: &mut Game) {
+        self.timeline.teardown(game);
     }
-    fn elapsed(&self, time: f32) -> IntroData {
-        IntroData {
-            time_left: self.time_left - time * INTRO_SPEED,
-            ..*self
+
+    fn update(&mut self, game: &mut Game) -> Transition {
+        self.timeline.update(game);
+        match self.timeline.finished() {
+            true => Transition::Replace(Box::new(PlayState::new())),
+            false => Transition::Stay,
         }
     }
-    fn waiting(&self) -> bool {
-        self.time_left > 0.0
-    }
-}
 
-#[derive(Debug)]
-pub enum IntroState {
-    Start,
-    Empty(IntroData),
-    Stars(IntroData),
-    Mother(IntroData),
-    MegaRay(IntroData),
-    TwinsRay(IntroData),
-    Twins(IntroData),
-    TwinsMoving(IntroData),
-    MotherLeaves(IntroData),
-    Ready(IntroData),
-    Set(IntroData),
-    Go,
+    fn cutscene_mut(&mut self) -> Option<&mut Timeline> {
+        Some(&mut self.timeline)
+    }
 }
 
-impl IntroState {
-    pub fn update(&self, game: &mut Game) -> IntroState {
-        match self {
-            &IntroState::Start => IntroState::Empty(IntroData::new().wait(20.0)),
-            &IntroState::Empty(ref d) if d.waiting() => {
-                IntroState::Empty(d.elapsed(game.delta_time()))
-            }
-            &IntroState::Empty(ref d) => {
-                game.add_entity(Box::new(Stars::new(10.0)));
-                game.add_entity(Box::new(Stars::new(20.0)));
-                IntroState::Stars(d.wait(5.0))
-            }
-            &IntroState::Stars(ref d) if d.waiting() => {
-                IntroState::Stars(d.elapsed(game.delta_time()))
-            }
-            &IntroState::Stars(ref d) => {
-                let d = IntroData {
-                    mother: Some(game.add_entity(Box::new(MotherIntro::new(Point2::new(
-                        200.0, 200.0,
-                    ))))),
-                    ..*d
-                };
-                IntroState::Mother(d.wait(5.0))
-            }
-            &IntroState::Mother(ref d) if d.waiting() => {
-                IntroState::Mother(d.elapsed(game.delta_time()))
-            }
-            &IntroState::Mother(ref d) => {
-                let d = IntroData {
-                    ray: Some(game.add_entity(Box::new(MegaRay::new(Point2::new(200.0, 200.0))))),
-                    ..*d
-                };
-                IntroState::MegaRay(d.wait(5.0))
-            }
-            &IntroState::MegaRay(ref d) if d.waiting() => {
-                IntroState::MegaRay(d.elapsed(game.delta_time()))
-            }
-            &IntroState::MegaRay(ref d) => {
-                let d = IntroData {
-                    twin1: Some(game.add_entity(Box::new(TwinIntro::new(Point2::new(
-                        200.0, 500.0,
-                    ))))),
-                    twin2: Some(game.add_entity(Box::new(TwinIntro::new(Point2::new(
-                        200.0, 500.0,
-                    ))))),
-                    ..*d
-                };
-                IntroState::TwinsRay(d.wait(5.0))
-            }
-            &IntroState::TwinsRay(ref d) if d.waiting() => {
-                IntroState::TwinsRay(d.elapsed(game.delta_time()))
-            }
-            &IntroState::TwinsRay(ref d) => {
-                if let Some(id) = d.ray {
-                    game.send_message(id, Message::Kill);
-                };
-                let d = IntroData { ray: None, ..*d };
-                IntroState::Twins(d.wait(5.0))
-            }
-            &IntroState::Twins(ref d) if d.waiting() => {
-                IntroState::Twins(d.elapsed(game.delta_time()))
-            }
-            &IntroState::Twins(ref d) => {
-                if let Some(id) = d.twin1 {
-                    game.send_message(id, Message::Move(Direction::Left, 100.0));
-                }
-                if let Some(id) = d.twin2 {
-                    game.send_message(id, Message::Move(Direction::Right, 100.0));
-                }
-                IntroState::TwinsMoving(d.wait(5.0))
-            }
-            &IntroState::TwinsMoving(ref d) if d.waiting() => {
-                IntroState::TwinsMoving(d.elapsed(game.delta_time()))
-            }
-            &IntroState::TwinsMoving(ref d) => {
-                if let Some(id) = d.mother {
-                    game.send_message(id, Message::Move(Direction::Up, 0.05 * INTRO_SPEED));
-                }
-                IntroState::MotherLeaves(d.wait(8.0))
-            }
-            &IntroState::MotherLeaves(ref d) if d.waiting() => {
-                IntroState::MotherLeaves(d.elapsed(game.delta_time()))
-            }
-            &IntroState::MotherLeaves(ref d) => {
-                if let Some(id) = d.mother {
-                    game.send_message(id, Message::Kill);
-                }
-                let d = IntroData { mother: None, ..*d };
-                game.add_entity(Box::new(Blink::new(0.5)));
-                IntroState::Ready(d.wait(2.0))
-            }
-            &IntroState::Ready(ref d) if d.waiting() => {
-                IntroState::Ready(d.elapsed(game.delta_time()))
-            }
-            &IntroState::Ready(ref d) => {
-                game.add_entity(Box::new(Blink::new(0.5)));
-                IntroState::Set(d.wait(2.0))
-            }
-            &IntroState::Set(ref d) if d.waiting() => IntroState::Set(d.elapsed(game.delta_time())),
-            &IntroState::Set(ref d) => {
-                game.add_entity(Box::new(Blink::new(2.0)));
-                if let Some(id) = d.twin1 {
-                    game.send_message(id, Message::Kill)
-                }
-                if let Some(id) = d.twin2 {
-                    game.send_message(id, Message::Kill)
-                }
+#[test]
+fn intro_golden_frames() {
+    use software::Canvas;
+    use {W_HEIGHT, W_WIDTH};
 
-                IntroState::Go
-            }
-            _ => IntroState::Go,
+    let mut game = Game::new();
+    let mut state = IntroState::new();
+    for frame in 1..361 {
+        game.update();
+        // The first frame only brought the intro up.
+        if frame > 1 {
+            state.update(&mut game);
+        }
+        if frame % 90 == 0 {
+            let mut canvas = Canvas::new(W_WIDTH, W_HEIGHT);
+            game.render(&mut canvas);
+            canvas.assert_golden(&format!("intro_{}", frame));
         }
     }
 }
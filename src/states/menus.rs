@@ -0,0 +1,323 @@
+//! The title screen and the menus pushed over the game.
+
+use engine::graphics::DrawPrimitives;
+use menu::{Menu, MenuEvent, MenuInput};
+use palette;
+use palette::Theme;
+use settings::{Bindings, Difficulty, Settings};
+use states::intro::IntroState;
+use states::play::{PlayState, Results};
+use states::{State, Transition};
+use Game;
+
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TitleAction {
+    Start,
+    Options,
+    Quit,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OptionsAction {
+    Controls,
+    Theme,
+    Difficulty,
+    Intro,
+    Back,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameOverAction {
+    Retry,
+    Title,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PauseAction {
+    Resume,
+    Options,
+    Quit,
+}
+
+const BINDINGS: [Bindings; 2] = [Bindings::Wasd, Bindings::Arrows];
+const DIFFICULTIES: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+fn themes() -> Vec<Theme> {
+    Theme::available(Path::new("themes"))
+}
+
+fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+fn title() -> Menu<TitleAction> {
+    Menu::new("TWINS")
+        .item("Start", TitleAction::Start)
+        .item("Options", TitleAction::Options)
+        .item("Quit", TitleAction::Quit)
+}
+
+fn pause() -> Menu<PauseAction> {
+    Menu::new("Paused")
+        .item("Resume", PauseAction::Resume)
+        .item("Options", PauseAction::Options)
+        .item("Quit", PauseAction::Quit)
+}
+
+fn game_over(results: Results) -> Menu<GameOverAction> {
+    Menu::new("Game over")
+        .note(&format!("Score {}", results.score))
+        .note(&format!("Survived {:.1}s", results.time))
+        .note(&format!("Shot {}, dodged {}", results.shot, results.dodged))
+        .item("Play again", GameOverAction::Retry)
+        .item("Title", GameOverAction::Title)
+}
+
+// Answers whether to quit.
+fn confirm_quit() -> Menu<bool> {
+    Menu::new("Quit the game?")
+        .item("No", false)
+        .item("Yes", true)
+}
+
+fn options(settings: &Settings) -> Menu<OptionsAction> {
+    let themes = themes();
+    let theme = themes
+        .iter()
+        .position(|theme| theme.name == settings.theme)
+        .unwrap_or(0);
+    Menu::new("Options")
+        .choice(
+            "Controls",
+            OptionsAction::Controls,
+            names(&["WASD", "Arrows"]),
+            BINDINGS
+                .iter()
+                .position(|&b| b == settings.bindings)
+                .unwrap_or(0),
+        )
+        .choice(
+            "Palette",
+            OptionsAction::Theme,
+            themes.into_iter().map(|theme| theme.name).collect(),
+            theme,
+        )
+        .choice(
+            "Difficulty",
+            OptionsAction::Difficulty,
+            names(&["Easy", "Normal", "Hard"]),
+            DIFFICULTIES
+                .iter()
+                .position(|&d| d == settings.difficulty)
+                .unwrap_or(1),
+        )
+        .choice(
+            "Intro",
+            OptionsAction::Intro,
+            names(&["Play", "Skip"]),
+            settings.skip_intro as usize,
+        )
+        .item("Back", OptionsAction::Back)
+}
+
+// Applies the choice picked for `action` and saves the settings.
+fn change(settings: &mut Settings, action: OptionsAction, choice: usize) {
+    match action {
+        OptionsAction::Controls => settings.bindings = BINDINGS[choice],
+        OptionsAction::Theme => {
+            if let Some(theme) = themes().into_iter().nth(choice) {
+                settings.theme = theme.name.clone();
+                palette::set_theme(theme);
+            }
+        }
+        OptionsAction::Difficulty => settings.difficulty = DIFFICULTIES[choice],
+        OptionsAction::Intro => settings.skip_intro = choice == 1,
+        OptionsAction::Back => return,
+    }
+    settings.save();
+}
+
+// Moves on to the next theme as if picked in the options, for the hotkey.
+pub fn next_theme(settings: &mut Settings) {
+    let themes = themes();
+    let current = themes.iter().position(|theme| theme.name == settings.theme);
+    let next = current.map_or(0, |i| (i + 1) % themes.len());
+    change(settings, OptionsAction::Theme, next);
+}
+
+#[derive(Debug)]
+pub struct TitleState {
+    menu: Menu<TitleAction>,
+}
+
+impl TitleState {
+    pub fn new() -> Self {
+        TitleState { menu: title() }
+    }
+}
+
+impl State for TitleState {
+    // Whatever the last run left behind goes away.
+    fn on_enter(&mut self, game: &mut Game) {
+        game.reset();
+    }
+
+    fn input(&mut self, game: &mut Game, input: MenuInput) -> Option<Transition> {
+        Some(match self.menu.input(input) {
+            Some(MenuEvent::Select(TitleAction::Start)) => {
+                Transition::Replace(Box::new(IntroState::new()))
+            }
+            Some(MenuEvent::Select(TitleAction::Options)) => {
+                Transition::Push(Box::new(OptionsState::new(&game.settings)))
+            }
+            Some(MenuEvent::Select(TitleAction::Quit)) | Some(MenuEvent::Back) => {
+                Transition::Push(Box::new(ConfirmQuitState::new()))
+            }
+            _ => Transition::Stay,
+        })
+    }
+
+    fn render(&self, surface: &mut DrawPrimitives) {
+        self.menu.render(surface);
+    }
+}
+
+#[derive(Debug)]
+pub struct OptionsState {
+    menu: Menu<OptionsAction>,
+}
+
+impl OptionsState {
+    pub fn new(settings: &Settings) -> Self {
+        OptionsState {
+            menu: options(settings),
+        }
+    }
+}
+
+impl State for OptionsState {
+    fn input(&mut self, game: &mut Game, input: MenuInput) -> Option<Transition> {
+        Some(match self.menu.input(input) {
+            Some(MenuEvent::Change(action, choice)) => {
+                change(&mut game.settings, action, choice);
+                Transition::Stay
+            }
+            Some(MenuEvent::Select(OptionsAction::Back)) | Some(MenuEvent::Back) => Transition::Pop,
+            _ => Transition::Stay,
+        })
+    }
+
+    fn render(&self, surface: &mut DrawPrimitives) {
+        self.menu.render(surface);
+    }
+
+    fn shows_below(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug)]
+pub struct PauseState {
+    menu: Menu<PauseAction>,
+}
+
+impl PauseState {
+    pub fn new() -> Self {
+        PauseState { menu: pause() }
+    }
+}
+
+impl State for PauseState {
+    fn input(&mut self, game: &mut Game, input: MenuInput) -> Option<Transition> {
+        Some(match self.menu.input(input) {
+            Some(MenuEvent::Select(PauseAction::Resume)) | Some(MenuEvent::Back) => Transition::Pop,
+            Some(MenuEvent::Select(PauseAction::Options)) => {
+                Transition::Push(Box::new(OptionsState::new(&game.settings)))
+            }
+            Some(MenuEvent::Select(PauseAction::Quit)) => {
+                Transition::Push(Box::new(ConfirmQuitState::new()))
+            }
+            _ => Transition::Stay,
+        })
+    }
+
+    fn render(&self, surface: &mut DrawPrimitives) {
+        self.menu.render(surface);
+    }
+
+    fn shows_below(&self) -> bool {
+        true
+    }
+
+    fn freezes_game(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug)]
+pub struct GameOverState {
+    menu: Menu<GameOverAction>,
+}
+
+impl GameOverState {
+    pub fn new(results: Results) -> Self {
+        GameOverState {
+            menu: game_over(results),
+        }
+    }
+}
+
+impl State for GameOverState {
+    fn input(&mut self, _game: &mut Game, input: MenuInput) -> Option<Transition> {
+        Some(match self.menu.input(input) {
+            Some(MenuEvent::Select(GameOverAction::Retry)) => {
+                Transition::Restart(Box::new(PlayState::new()))
+            }
+            Some(MenuEvent::Select(GameOverAction::Title)) => {
+                Transition::Restart(Box::new(TitleState::new()))
+            }
+            _ => Transition::Stay,
+        })
+    }
+
+    fn render(&self, surface: &mut DrawPrimitives) {
+        self.menu.render(surface);
+    }
+
+    fn shows_below(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug)]
+pub struct ConfirmQuitState {
+    menu: Menu<bool>,
+}
+
+impl ConfirmQuitState {
+    pub fn new() -> Self {
+        ConfirmQuitState {
+            menu: confirm_quit(),
+        }
+    }
+}
+
+impl State for ConfirmQuitState {
+    fn input(&mut self, _game: &mut Game, input: MenuInput) -> Option<Transition> {
+        Some(match self.menu.input(input) {
+            Some(MenuEvent::Select(true)) => Transition::Quit,
+            Some(MenuEvent::Select(false)) | Some(MenuEvent::Back) => Transition::Pop,
+            _ => Transition::Stay,
+        })
+    }
+
+    fn render(&self, surface: &mut DrawPrimitives) {
+        self.menu.render(surface);
+    }
+
+    fn shows_below(&self) -> bool {
+        true
+    }
+}
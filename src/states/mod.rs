@@ -1,28 +1,225 @@
+//! Game states kept on a stack: the top one is updated and gets the input,
+//! overlays like the pause menu are pushed over play without ending it.
+
 pub mod intro;
+pub mod menus;
 pub mod play;
 
-use states::intro::IntroState;
-use states::play::PlayState;
+use cutscene::Timeline;
+use engine::graphics::DrawPrimitives;
+use menu::MenuInput;
 use Game;
 
-#[derive(Debug)]
-pub enum GameState {
-    Start,
-    Intro(IntroState),
-    Play(PlayState),
-    _Halt,
+pub enum Transition {
+    Stay,
+    // Pauses the top state and puts another over it.
+    Push(Box<State>),
+    // Ends the top state and resumes the one below.
+    Pop,
+    // Ends the top state for another, the ones below stay paused.
+    Replace(Box<State>),
+    // Ends every state and starts over with another, from a fresh game with
+    // the same random enemies.
+    Restart(Box<State>),
+    Quit,
 }
 
-impl GameState {
-    pub fn update(&self, game: &mut Game) -> GameState {
-        match self {
-            &GameState::Start => GameState::Intro(IntroState::Start),
-            &GameState::Intro(ref intro_state) => match intro_state.update(game) {
-                IntroState::Go => GameState::Play(PlayState::new()),
-                x => GameState::Intro(x),
-            },
-            &GameState::Play(ref state) => GameState::Play(state.update(game)),
-            &GameState::_Halt => GameState::_Halt,
+pub trait State {
+    fn on_enter(&mut self, _game: &mut Game) {}
+    fn on_exit(&mut self, _game: &mut Game) {}
+    // Another state was pushed over this one.
+    fn on_pause(&mut self, _game: &mut Game) {}
+    fn on_resume(&mut self, _game: &mut Game) {}
+
+    // Only called while on top.
+    fn update(&mut self, _game: &mut Game) -> Transition {
+        Transition::Stay
+    }
+    // `None` leaves the input to the controller.
+    fn input(&mut self, _game: &mut Game, _input: MenuInput) -> Option<Transition> {
+        None
+    }
+    fn render(&self, _surface: &mut DrawPrimitives) {}
+
+    // Whether the states below still render under this one.
+    fn shows_below(&self) -> bool {
+        false
+    }
+    // Whether entities stand still while this state is on the stack.
+    fn freezes_game(&self) -> bool {
+        false
+    }
+    fn cutscene_mut(&mut self) -> Option<&mut Timeline> {
+        None
+    }
+}
+
+pub struct StateStack {
+    states: Vec<Box<State>>,
+}
+
+impl StateStack {
+    pub fn new() -> Self {
+        StateStack { states: Vec::new() }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.states.len()
+    }
+
+    // Nothing left to run.
+    pub fn finished(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    pub fn runs_game(&self) -> bool {
+        !self.states.iter().any(|state| state.freezes_game())
+    }
+
+    pub fn update(&mut self, game: &mut Game) {
+        let transition = match self.states.last_mut() {
+            Some(top) => top.update(game),
+            None => Transition::Stay,
+        };
+        self.apply(game, transition);
+    }
+
+    // Returns whether the top state used the input.
+    pub fn input(&mut self, game: &mut Game, input: MenuInput) -> bool {
+        let transition = match self.states.last_mut() {
+            Some(top) => top.input(game, input),
+            None => None,
+        };
+        match transition {
+            Some(transition) => {
+                self.apply(game, transition);
+                true
+            }
+            None => false,
         }
     }
+
+    // From the lowest state still showing up to the top.
+    pub fn render(&self, surface: &mut DrawPrimitives) {
+        let mut first = self.states.len().saturating_sub(1);
+        while first > 0 && self.states[first].shows_below() {
+            first -= 1;
+        }
+        for state in self.states.iter().skip(first) {
+            state.render(surface);
+        }
+    }
+
+    pub fn cutscene_mut(&mut self) -> Option<&mut Timeline> {
+        self.states.last_mut().and_then(|top| top.cutscene_mut())
+    }
+
+    pub fn apply(&mut self, game: &mut Game, transition: Transition) {
+        match transition {
+            Transition::Stay => (),
+            Transition::Push(state) => {
+                if let Some(top) = self.states.last_mut() {
+                    top.on_pause(game);
+                }
+                self.push(game, state);
+            }
+            Transition::Pop => {
+                self.pop(game);
+                if let Some(top) = self.states.last_mut() {
+                    top.on_resume(game);
+                }
+            }
+            Transition::Replace(state) => {
+                self.pop(game);
+                self.push(game, state);
+            }
+            Transition::Restart(state) => {
+                while !self.states.is_empty() {
+                    self.pop(game);
+                }
+                game.reset();
+                self.push(game, state);
+            }
+            Transition::Quit => {
+                while !self.states.is_empty() {
+                    self.pop(game);
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, game: &mut Game, mut state: Box<State>) {
+        state.on_enter(game);
+        self.states.push(state);
+    }
+
+    fn pop(&mut self, game: &mut Game) {
+        if let Some(mut state) = self.states.pop() {
+            state.on_exit(game);
+        }
+    }
+}
+
+#[test]
+fn stack_calls_hooks_in_order() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct Probe(&'static str, Rc<RefCell<Vec<String>>>);
+    impl State for Probe {
+        fn on_enter(&mut self, _game: &mut Game) {
+            self.1.borrow_mut().push(format!("enter {}", self.0));
+        }
+        fn on_exit(&mut self, _game: &mut Game) {
+            self.1.borrow_mut().push(format!("exit {}", self.0));
+        }
+        fn on_pause(&mut self, _game: &mut Game) {
+            self.1.borrow_mut().push(format!("pause {}", self.0));
+        }
+        fn on_resume(&mut self, _game: &mut Game) {
+            self.1.borrow_mut().push(format!("resume {}", self.0));
+        }
+    }
+
+    let mut game = Game::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let probe = |name| Box::new(Probe(name, log.clone()));
+    let mut stack = StateStack::new();
+    stack.apply(&mut game, Transition::Push(probe("a")));
+    stack.apply(&mut game, Transition::Push(probe("b")));
+    stack.apply(&mut game, Transition::Replace(probe("c")));
+    stack.apply(&mut game, Transition::Pop);
+    stack.apply(&mut game, Transition::Quit);
+    assert!(stack.finished());
+    assert_eq!(
+        *log.borrow(),
+        vec!["enter a", "pause a", "enter b", "exit b", "enter c", "exit c", "resume a", "exit a"]
+    );
+}
+
+#[test]
+fn pausing_and_quitting() {
+    use entities::EntityTag;
+    use states::play::PlayState;
+
+    let mut game = Game::new();
+    let mut stack = StateStack::new();
+    stack.apply(&mut game, Transition::Push(Box::new(PlayState::new())));
+    stack.update(&mut game);
+    assert!(!stack.input(&mut game, MenuInput::Up));
+    assert!(stack.input(&mut game, MenuInput::Back));
+    assert_eq!(stack.depth(), 2);
+    assert!(!stack.runs_game());
+    stack.input(&mut game, MenuInput::Select);
+    assert_eq!(stack.depth(), 1);
+    assert!(stack.runs_game());
+
+    stack.input(&mut game, MenuInput::Back);
+    stack.input(&mut game, MenuInput::Up);
+    stack.input(&mut game, MenuInput::Select);
+    assert_eq!(stack.depth(), 3);
+    stack.input(&mut game, MenuInput::Down);
+    stack.input(&mut game, MenuInput::Select);
+    assert!(stack.finished());
+    assert!(game.positions(EntityTag::Stars).is_empty());
 }
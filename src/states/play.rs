@@ -3,9 +3,19 @@ use ggez::graphics::Point2;
 use math::VectorUtils;
 
 use bezier2::Bezier;
+use engine::graphics::DrawPrimitives;
+use entities::particles::{Emitter, EmitterConfig};
+use entities::stars::Stars;
 use entities::{enemy, twin};
-use entities::{EntityId, EntityTag};
+use entities::{EntityId, EntityTag, EntityTagPlayer};
+use hud::{Hud, TwinStatus};
+use math::Randomize;
+use menu::MenuInput;
 use messages::{Direction, Message, SendMessageTo};
+use playfield;
+use rand::StdRng;
+use states::menus::{GameOverState, PauseState};
+use states::{State, Transition};
 
 use std::time::Duration;
 
@@ -17,63 +27,279 @@ impl EnemyPath for Bezier {
     }
 }
 
-#[derive(Debug, Clone)]
-enum State {
-    Start,
-    Normal,
+/// How a run went, shown once the twins are out of lives.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Results {
+    pub time: f32,
+    pub dodged: u32,
+    pub shot: u32,
+    pub score: u32,
 }
 
-#[derive(Debug, Clone)]
+// A twin and how it's doing, `id` is gone once it's out of lives.
+#[derive(Debug)]
+struct Pilot {
+    id: Option<EntityId>,
+    player: EntityTagPlayer,
+    status: TwinStatus,
+    // Seconds left in which it can't be hit again.
+    invulnerable: f32,
+}
+
+#[derive(Debug)]
 pub struct PlayState {
-    state: State,
+    // Thrusters and the stars, torn down with the twins and the enemies.
+    spawned: Vec<EntityId>,
+    pilots: Vec<Pilot>,
     enemies: Vec<EntityId>,
     tenemy: f32,
+    // Enemies sent so far, every WAVE_SIZE of them starts a new wave.
+    sent: u32,
+    hud: Hud,
+    results: Results,
 }
 
 const ENEMY_INTERVAL: f32 = 6.0;
+const WAVE_SIZE: u32 = 5;
+// Each wave sends enemies this much more often than the last.
+const WAVE_SPEEDUP: f32 = 0.85;
+// Each twin's.
+const LIVES: u32 = 3;
+// Seconds after a hit in which a twin can't be hit again.
+const INVULNERABLE_TIME: f32 = 2.0;
+const TWIN_RADIUS: f32 = 20.0;
+const HIT_TRAUMA: f32 = 0.6;
+// About half of how far a shot flies in a frame, so it can't skip past parts.
+const SHOT_RADIUS: f32 = 10.0;
+const SHOT_DAMAGE: f32 = 5.0;
+const KILL_SCORE: u32 = 100;
+// Score needed for each weapon level past the first.
+const WEAPON_STEP: u32 = 1000;
+const DEATH_SPARKS: usize = 80;
+// How far below each twin its thruster sits.
+const THRUSTER_DROP: f32 = 12.0;
+// How far the camera looks ahead of the twins, as a fraction of the playfield
+// height, so they stay low on screen.
+const CAMERA_LEAD: f32 = 1.0 / 3.0;
 
 impl PlayState {
     pub fn new() -> Self {
         Self {
-            state: State::Start,
+            spawned: Vec::new(),
+            pilots: Vec::new(),
             enemies: Vec::new(),
             tenemy: ENEMY_INTERVAL,
+            sent: 0,
+            hud: Hud::new(),
+            results: Results {
+                time: 0.0,
+                dodged: 0,
+                shot: 0,
+                score: 0,
+            },
         }
     }
-    pub fn update(&self, game: &mut Game) -> Self {
-        let mut new = self.clone();
-        new.state = match self.state {
-            State::Start => {
-                game.send_message(EntityTag::Stars, Message::Move(Direction::Down, 2.0));
-
-                game.add_entity(Box::new(twin::Twin::new(
-                    Point2::new(100.0, 500.0),
-                    twin::Player::One,
-                )));
-                game.add_entity(Box::new(twin::Twin::new(
-                    Point2::new(300.0, 500.0),
-                    twin::Player::Two,
-                )));
-
-                State::Normal
+    fn wave(&self) -> u32 {
+        1 + self.sent / WAVE_SIZE
+    }
+    fn add_twin(&mut self, game: &mut Game, pos: Point2, player: twin::Player) {
+        let tag = match player {
+            twin::Player::One => EntityTagPlayer::One,
+            twin::Player::Two => EntityTagPlayer::Two,
+        };
+        let id = game.add_entity(Box::new(twin::Twin::new(pos, player)));
+        let thruster = Emitter::new(pos, EmitterConfig::thruster())
+            .attach(id, Point2::new(0.0, THRUSTER_DROP))
+            .seeded(&mut game.rng);
+        self.pilots.push(Pilot {
+            id: Some(id),
+            player: tag,
+            status: TwinStatus {
+                score: 0,
+                lives: LIVES,
+                weapon: 1,
+            },
+            invulnerable: 0.0,
+        });
+        self.spawned.push(game.add_entity(Box::new(thruster)));
+    }
+    // Takes a life from each twin an enemy touches, returns whether both
+    // are out of lives.
+    fn check_hits(&mut self, game: &mut Game) -> bool {
+        for pilot in self.pilots.iter_mut() {
+            pilot.invulnerable -= game.delta_time();
+            let id = match pilot.id {
+                Some(id) if pilot.invulnerable <= 0.0 => id,
+                _ => continue,
+            };
+            let pos = match game.position_of(id) {
+                Some(pos) => pos,
+                None => continue,
+            };
+            if game.hit_query(EntityTag::Enemy, pos, TWIN_RADIUS).is_none() {
+                continue;
             }
-            State::Normal => {
-                new.tenemy -= game.delta_time();
-                if new.tenemy <= 0.0 {
-                    new.tenemy += ENEMY_INTERVAL;
-                    new.enemies.push(game.add_entity(Box::new(enemy::Enemy::new(
-                        PlayState::random_path(),
-                        Duration::from_secs(3),
-                    ))));
+            pilot.status.lives = pilot.status.lives.saturating_sub(1);
+            pilot.invulnerable = INVULNERABLE_TIME;
+            game.camera.add_trauma(HIT_TRAUMA);
+            if pilot.status.lives > 0 {
+                continue;
+            }
+            let sparks = Emitter::new(pos, EmitterConfig::sparks())
+                .burst(DEATH_SPARKS)
+                .lasting(0.0)
+                .seeded(&mut game.rng);
+            game.add_entity(Box::new(sparks));
+            game.remove_entity(id);
+            pilot.id = None;
+        }
+        self.pilots.iter().all(|pilot| pilot.id.is_none())
+    }
+    // Shots are spent on the first enemy they hit, kills score for the twin
+    // that fired and level up its weapon.
+    fn check_shots(&mut self, game: &mut Game) {
+        for pilot in self.pilots.iter_mut() {
+            for (shot, pos) in game.tagged(EntityTag::Shot(pilot.player)) {
+                let (enemy, node) = match game.hit_query(EntityTag::Enemy, pos, SHOT_RADIUS) {
+                    Some(hit) => hit,
+                    None => continue,
+                };
+                game.remove_entity(shot);
+                game.send_message(enemy, Message::Damage(node, SHOT_DAMAGE));
+                if game.is_alive(enemy) {
+                    continue;
+                }
+                let at = game.position_of(enemy).unwrap_or(pos);
+                self.hud.popup(&format!("+{}", KILL_SCORE), at);
+                self.enemies.retain(|&id| id != enemy);
+                self.results.shot += 1;
+                pilot.status.score += KILL_SCORE;
+                let weapon = (1 + pilot.status.score / WEAPON_STEP).min(twin::MAX_WEAPON);
+                if weapon != pilot.status.weapon {
+                    pilot.status.weapon = weapon;
+                    if let Some(id) = pilot.id {
+                        game.send_message(id, Message::Weapon(weapon));
+                    }
                 }
-                State::Normal
             }
+        }
+    }
+    // Dives down to the twins' row and back up, from either side.
+    fn random_path(rng: &mut StdRng) -> Bezier {
+        let side = match 0.0f32.rand(rng, 1.0) > 0.0 {
+            true => 1.0,
+            false => -1.0,
         };
-        new
+        let bottom = 0.5f32.rand(rng, 0.35);
+        let across = Point2::right().mul(side * playfield::width() / 4.0);
+        let dive = Point2::down().mul(playfield::height() / 2.0);
+        Bezier::from(playfield::at(0.5 - side * 0.75, -1.0 / 6.0), dive)
+            .to(playfield::at(bottom, 5.0 / 6.0), across)
+            .to(playfield::at(0.5 + side * 0.75, -1.0 / 6.0), dive.mul(-1.0))
+    }
+}
+
+impl State for PlayState {
+    fn on_enter(&mut self, game: &mut Game) {
+        for &distance in [10.0, 20.0].iter() {
+            self.spawned
+                .push(game.add_entity(Box::new(Stars::new(distance))));
+        }
+        game.send_message(EntityTag::Stars, Message::Move(Direction::Down, 2.0));
+
+        self.add_twin(game, playfield::at(0.25, 5.0 / 6.0), twin::Player::One);
+        self.add_twin(game, playfield::at(0.75, 5.0 / 6.0), twin::Player::Two);
+    }
+
+    fn on_exit(&mut self, game: &mut Game) {
+        let twins = self.pilots.iter().filter_map(|pilot| pilot.id);
+        for id in self.spawned.iter().cloned().chain(twins) {
+            game.remove_entity(id);
+        }
+        for &id in self.enemies.iter() {
+            game.remove_entity(id);
+        }
+    }
+
+    fn update(&mut self, game: &mut Game) -> Transition {
+        self.results.time += game.delta_time();
+        self.tenemy -= game.delta_time();
+        if self.tenemy <= 0.0 {
+            let wave = self.wave();
+            let speedup = WAVE_SPEEDUP.powi(wave as i32 - 1);
+            self.tenemy += ENEMY_INTERVAL * game.settings.difficulty.enemy_interval() * speedup;
+            let path = PlayState::random_path(&mut game.rng);
+            let enemy = enemy::Enemy::new(path, Duration::from_secs(3)).seeded(&mut game.rng);
+            self.enemies.push(game.add_entity(Box::new(enemy)));
+            self.sent += 1;
+            if self.wave() > wave {
+                let center = game.camera.transform().inverse().apply(playfield::center());
+                self.hud.popup(&format!("WAVE {}", self.wave()), center);
+            }
+        }
+        let twins = game.positions(EntityTag::Player(EntityTagPlayer::Both));
+        if twins.len() > 0 {
+            let sum = twins.iter().fold(Point2::zero(), |sum, p| sum.add(*p));
+            let midpoint = sum.mul(1.0 / twins.len() as f32);
+            let lead = Point2::up().mul(CAMERA_LEAD * playfield::height());
+            game.camera.follow(Some(midpoint.add(lead)));
+        }
+        self.check_shots(game);
+        let before = self.enemies.len();
+        self.enemies.retain(|id| game.position_of(*id).is_some());
+        self.results.dodged += (before - self.enemies.len()) as u32;
+        for id in self.enemies.iter() {
+            let pos = game.position_of(*id).unwrap();
+            let both = EntityTag::Player(EntityTagPlayer::Both);
+            if let Some(target) = game.nearest(both, pos) {
+                game.send_message(*id, Message::Target(target));
+            }
+        }
+        let out = self.check_hits(game);
+        self.results.score = self.pilots.iter().map(|pilot| pilot.status.score).sum();
+        self.hud.twins = self.pilots.iter().map(|pilot| pilot.status).collect();
+        self.hud.wave = self.wave();
+        self.hud.update(game.delta_time(), game.camera.transform());
+        match out {
+            true => Transition::Push(Box::new(GameOverState::new(self.results))),
+            false => Transition::Stay,
+        }
     }
-    fn random_path() -> Bezier {
-        Bezier::from(Point2::new(-100.0, -100.0), Point2::right().mul(100.0))
-            .to(Point2::new(200.0, 200.0), Point2::up().mul(300.0))
-            .to(Point2::new(500.0, 500.0), Point2::right().mul(100.0))
+
+    fn input(&mut self, _game: &mut Game, input: MenuInput) -> Option<Transition> {
+        match input {
+            MenuInput::Back => Some(Transition::Push(Box::new(PauseState::new()))),
+            _ => None,
+        }
+    }
+
+    fn render(&self, surface: &mut DrawPrimitives) {
+        self.hud.render(surface);
+    }
+}
+
+#[test]
+fn runs_tear_down_and_restart_the_same() {
+    use states::StateStack;
+
+    let mut game = Game::new();
+    let mut play = PlayState::new();
+    play.on_enter(&mut game);
+    for _ in 0..200 {
+        play.update(&mut game);
+        game.update();
+    }
+    assert_eq!(game.positions(EntityTag::Stars).len(), 2);
+    let first = PlayState::random_path(&mut game.rng).get(0.5);
+    play.on_exit(&mut game);
+    assert!(game.positions(EntityTag::Stars).is_empty());
+    assert!(game.positions(EntityTag::Enemy).is_empty());
+
+    let mut stack = StateStack::new();
+    stack.apply(&mut game, Transition::Restart(Box::new(PlayState::new())));
+    for _ in 0..200 {
+        stack.update(&mut game);
+        game.update();
     }
+    assert_eq!(PlayState::random_path(&mut game.rng).get(0.5), first);
 }
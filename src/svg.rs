@@ -0,0 +1,270 @@
+use engine::graphics::{Color, DrawMode, DrawPrimitives, Graphics, Transform, TransformStack};
+use nalgebra::Point2;
+
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+
+pub struct SvgDocument {
+    width: u32,
+    height: u32,
+    color: [f32; 4],
+    elements: Vec<String>,
+    transforms: TransformStack,
+}
+
+impl SvgDocument {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            color: [1.0, 1.0, 1.0, 1.0],
+            elements: Vec::new(),
+            transforms: TransformStack::new(),
+        }
+    }
+
+    pub fn document(&self) -> String {
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+            self.width, self.height, self.width, self.height
+        );
+        for element in self.elements.iter() {
+            out.push_str("  ");
+            out.push_str(element);
+            out.push('\n');
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        File::create(path)?.write_all(self.document().as_bytes())
+    }
+
+    fn paint(&self, mode: DrawMode) -> String {
+        match mode {
+            DrawMode::Fill => format!(
+                "fill=\"{}\"{}",
+                hex(self.color),
+                opacity("fill-opacity", self.color)
+            ),
+            DrawMode::Line(width) => self.stroke(width),
+        }
+    }
+
+    // Shapes keep their local coordinates, SVG applies the transform itself.
+    fn push(&mut self, element: String) {
+        let t = self.transforms.current();
+        let element = match t == Transform::identity() {
+            true => element,
+            false => element.replacen(
+                ' ',
+                &format!(
+                    " transform=\"matrix({} {} {} {} {} {})\" ",
+                    num(t.a),
+                    num(t.b),
+                    num(t.c),
+                    num(t.d),
+                    num(t.tx),
+                    num(t.ty)
+                ),
+                1,
+            ),
+        };
+        self.elements.push(element);
+    }
+
+    fn stroke(&self, width: f32) -> String {
+        format!(
+            "fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"{}",
+            hex(self.color),
+            num(width),
+            opacity("stroke-opacity", self.color)
+        )
+    }
+}
+
+// Anything past this is off the page anyway.
+const LIMIT: f32 = 1.0e6;
+
+// NaN and infinities aren't SVG numbers, so like the software backend they
+// land at zero or at the far edge.
+fn num(value: f32) -> String {
+    let value = match value.is_nan() {
+        true => 0.0,
+        false => value.max(-LIMIT).min(LIMIT),
+    };
+    let s = format!("{:.2}", value);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    match s {
+        "-0" => "0".to_string(),
+        s => s.to_string(),
+    }
+}
+
+fn hex(color: [f32; 4]) -> String {
+    let byte = |v: f32| (v.max(0.0).min(1.0) * 255.0).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        byte(color[0]),
+        byte(color[1]),
+        byte(color[2])
+    )
+}
+
+fn opacity(attribute: &str, color: [f32; 4]) -> String {
+    match color[3] < 1.0 {
+        true => format!(" {}=\"{}\"", attribute, num(color[3].max(0.0))),
+        false => String::new(),
+    }
+}
+
+fn points(points: &[Point2<f32>]) -> String {
+    let points: Vec<String> = points
+        .iter()
+        .map(|p| format!("{},{}", num(p.x), num(p.y)))
+        .collect();
+    points.join(" ")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl Graphics for SvgDocument {
+    fn clear(&mut self, color: &Color) {
+        let color = color.into_rgba();
+        self.elements.clear();
+        self.elements.push(format!(
+            "<rect width=\"100%\" height=\"100%\" fill=\"{}\"{}/>",
+            hex(color),
+            opacity("fill-opacity", color)
+        ));
+    }
+    fn present(&mut self) {}
+}
+
+impl DrawPrimitives for SvgDocument {
+    fn transform_stack(&mut self) -> &mut TransformStack {
+        &mut self.transforms
+    }
+    fn set_color(&mut self, color: &Color) {
+        self.color = color.into_rgba();
+    }
+    fn circle(&mut self, mode: DrawMode, origin: Point2<f32>, radius: f32) {
+        let element = format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" {}/>",
+            num(origin.x),
+            num(origin.y),
+            num(radius),
+            self.paint(mode)
+        );
+        self.push(element);
+    }
+    fn ellipse(&mut self, mode: DrawMode, origin: Point2<f32>, width: f32, height: f32) {
+        let element = format!(
+            "<ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\" {}/>",
+            num(origin.x),
+            num(origin.y),
+            num(width),
+            num(height),
+            self.paint(mode)
+        );
+        self.push(element);
+    }
+    fn line(&mut self, origin: Point2<f32>, target: Point2<f32>, width: f32) {
+        let element = format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {}/>",
+            num(origin.x),
+            num(origin.y),
+            num(target.x),
+            num(target.y),
+            self.stroke(width)
+        );
+        self.push(element);
+    }
+    fn text(&mut self, text: &str, origin: Point2<f32>) {
+        let origin = self.transforms.current().apply(origin);
+        let element = format!(
+            "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"12\" dominant-baseline=\"hanging\" fill=\"{}\"{}>{}</text>",
+            num(origin.x),
+            num(origin.y),
+            hex(self.color),
+            opacity("fill-opacity", self.color),
+            escape(text)
+        );
+        self.elements.push(element);
+    }
+    fn polygon(&mut self, mode: DrawMode, points: &[Point2<f32>]) {
+        let element = format!(
+            "<polygon points=\"{}\" fill-rule=\"evenodd\" stroke-linejoin=\"round\" {}/>",
+            self::points(points),
+            self.paint(mode)
+        );
+        self.push(element);
+    }
+    fn polyline(&mut self, points: &[Point2<f32>], width: f32) {
+        let element = format!(
+            "<polyline points=\"{}\" stroke-linejoin=\"round\" {}/>",
+            self::points(points),
+            self.stroke(width)
+        );
+        self.push(element);
+    }
+    fn rectangle(&mut self, mode: DrawMode, origin: Point2<f32>, width: f32, height: f32) {
+        let element = format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>",
+            num(origin.x),
+            num(origin.y),
+            num(width),
+            num(height),
+            self.paint(mode)
+        );
+        self.push(element);
+    }
+}
+
+#[test]
+fn svg_document_keeps_style() {
+    let mut svg = SvgDocument::new(40, 30);
+    svg.clear(&[0.0, 0.0, 0.0, 1.0]);
+    svg.set_color(&[1.0, 0.5, 0.0, 0.25]);
+    svg.circle(DrawMode::Line(1.5), Point2::new(10.0, 12.5), 4.0);
+    svg.set_color(&[1.0, 1.0, 1.0, 1.0]);
+    svg.line(Point2::new(0.0, 0.0), Point2::new(40.0, 30.0), 2.0);
+    svg.text("a<b", Point2::new(1.0, 2.0));
+    assert_eq!(
+        svg.document(),
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"40\" height=\"30\" viewBox=\"0 0 40 30\">\n\
+         \x20 <rect width=\"100%\" height=\"100%\" fill=\"#000000\"/>\n\
+         \x20 <circle cx=\"10\" cy=\"12.5\" r=\"4\" fill=\"none\" stroke=\"#ff8000\" stroke-width=\"1.5\" stroke-opacity=\"0.25\"/>\n\
+         \x20 <line x1=\"0\" y1=\"0\" x2=\"40\" y2=\"30\" fill=\"none\" stroke=\"#ffffff\" stroke-width=\"2\"/>\n\
+         \x20 <text x=\"1\" y=\"2\" font-family=\"monospace\" font-size=\"12\" dominant-baseline=\"hanging\" fill=\"#ffffff\">a&lt;b</text>\n\
+         </svg>\n"
+    );
+
+    assert_eq!(num(::std::f32::NAN), "0");
+    assert_eq!(num(::std::f32::NEG_INFINITY), "-1000000");
+}
+
+#[test]
+fn svg_document_keeps_transforms() {
+    let mut svg = SvgDocument::new(40, 30);
+    svg.push_transform(Transform::translate(10.0, 5.0));
+    svg.push_transform(Transform::scale(2.0, 2.0));
+    svg.circle(DrawMode::Fill, Point2::new(1.0, 1.0), 3.0);
+    svg.text("hi", Point2::new(1.0, 1.0));
+    svg.pop_transform();
+    svg.pop_transform();
+    assert_eq!(
+        svg.elements,
+        vec![
+            "<circle transform=\"matrix(2 0 0 2 10 5)\" cx=\"1\" cy=\"1\" r=\"3\" fill=\"#ffffff\"/>",
+            "<text x=\"12\" y=\"7\" font-family=\"monospace\" font-size=\"12\" dominant-baseline=\"hanging\" fill=\"#ffffff\">hi</text>",
+        ]
+    );
+}
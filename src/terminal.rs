@@ -0,0 +1,399 @@
+//! Renders frames as Unicode braille on an ANSI terminal.
+//!
+//! Every character cell holds 2x4 dots and a single 24-bit foreground color,
+//! the color of the last primitive drawn over it. Keys are read from stdin on
+//! a separate thread once the terminal is switched to raw mode.
+
+use engine::graphics::{
+    polygon_contains, segment_distance, Color, DrawMode, DrawPrimitives, Graphics, MappedEllipse,
+    TransformStack,
+};
+use nalgebra::Point2;
+
+use std::io;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+
+const DOTS_X: usize = 2;
+const DOTS_Y: usize = 4;
+const BRAILLE: u32 = 0x2800;
+const DOT_BITS: [[u8; DOTS_Y]; DOTS_X] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Key {
+    Char(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Backspace,
+    Escape,
+}
+
+pub struct Terminal {
+    columns: usize,
+    rows: usize,
+    // dots per logical unit
+    scale: (f32, f32),
+    color: [f32; 4],
+    background: [f32; 4],
+    dots: Vec<u8>,
+    colors: Vec<[f32; 4]>,
+    letters: Vec<Option<char>>,
+    transforms: TransformStack,
+    pub keys: Vec<Key>,
+}
+
+impl Terminal {
+    pub fn new(width: f32, height: f32, columns: usize, rows: usize) -> Self {
+        Self {
+            columns,
+            rows,
+            scale: (
+                (columns * DOTS_X) as f32 / width,
+                (rows * DOTS_Y) as f32 / height,
+            ),
+            color: [1.0, 1.0, 1.0, 1.0],
+            background: [0.0, 0.0, 0.0, 1.0],
+            dots: vec![0; columns * rows],
+            colors: vec![[1.0, 1.0, 1.0, 1.0]; columns * rows],
+            letters: vec![None; columns * rows],
+            transforms: TransformStack::new(),
+            keys: Vec::new(),
+        }
+    }
+
+    // Fits the logical size in the current terminal, leaving the last row free.
+    pub fn fit(width: f32, height: f32) -> Self {
+        let (columns, rows) = size().unwrap_or((80, 24));
+        Self::new(width, height, columns, rows.max(2) - 1)
+    }
+
+    // The frame as text with ANSI color sequences, cursor starting at home.
+    pub fn frame(&self) -> String {
+        let mut out = String::from("\x1b[H");
+        out.push_str(&format!("\x1b[48;2;{}m", rgb(self.background)));
+        let mut last = None;
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let i = row * self.columns + column;
+                let color = rgb(self.colors[i]);
+                if last.as_ref() != Some(&color) {
+                    out.push_str(&format!("\x1b[38;2;{}m", color));
+                    last = Some(color);
+                }
+                let c = match self.letters[i] {
+                    Some(c) => c,
+                    None => ::std::char::from_u32(BRAILLE + self.dots[i] as u32).unwrap(),
+                };
+                out.push(c);
+            }
+            out.push_str("\r\n");
+        }
+        out.push_str("\x1b[0m");
+        out
+    }
+
+    fn plot(&mut self, x: usize, y: usize) {
+        let i = (y / DOTS_Y) * self.columns + x / DOTS_X;
+        self.dots[i] |= DOT_BITS[x % DOTS_X][y % DOTS_Y];
+        self.colors[i] = blend(self.background, self.color);
+    }
+
+    // Sets every dot whose center (in logical units) is inside the shape
+    // described by `inside`, only looking at dots within the bounding box.
+    fn fill<F>(&mut self, min: Point2<f32>, max: Point2<f32>, inside: F)
+    where
+        F: Fn(Point2<f32>) -> bool,
+    {
+        let (sx, sy) = self.scale;
+        let x0 = (min.x * sx).floor().max(0.0) as usize;
+        let y0 = (min.y * sy).floor().max(0.0) as usize;
+        let x1 = ((max.x * sx).ceil().max(0.0) as usize).min(self.columns * DOTS_X);
+        let y1 = ((max.y * sy).ceil().max(0.0) as usize).min(self.rows * DOTS_Y);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let p = Point2::new((x as f32 + 0.5) / sx, (y as f32 + 0.5) / sy);
+                if inside(p) {
+                    self.plot(x, y);
+                }
+            }
+        }
+    }
+
+    // Half the size of a dot in logical units, so thin lines never vanish.
+    fn dot_radius(&self) -> f32 {
+        0.5 / self.scale.0.min(self.scale.1)
+    }
+}
+
+fn rgb(color: [f32; 4]) -> String {
+    let byte = |v: f32| (v.max(0.0).min(1.0) * 255.0).round() as u8;
+    format!("{};{};{}", byte(color[0]), byte(color[1]), byte(color[2]))
+}
+
+fn blend(background: [f32; 4], color: [f32; 4]) -> [f32; 4] {
+    let a = color[3].max(0.0).min(1.0);
+    let mix = |b: f32, c: f32| b * (1.0 - a) + c * a;
+    [
+        mix(background[0], color[0]),
+        mix(background[1], color[1]),
+        mix(background[2], color[2]),
+        1.0,
+    ]
+}
+
+impl Graphics for Terminal {
+    fn clear(&mut self, color: &Color) {
+        self.background = color.into_rgba();
+        for i in 0..self.dots.len() {
+            self.dots[i] = 0;
+            self.colors[i] = self.background;
+            self.letters[i] = None;
+        }
+    }
+    fn present(&mut self) {
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        out.write_all(self.frame().as_bytes()).unwrap();
+        out.flush().unwrap();
+    }
+}
+
+// Primitives in logical coordinates, after the transform was applied.
+impl Terminal {
+    fn draw_ellipse(&mut self, mode: DrawMode, origin: Point2<f32>, width: f32, height: f32) {
+        let half = match mode {
+            DrawMode::Line(w) => (w / 2.0).max(self.dot_radius()),
+            DrawMode::Fill => 0.0,
+        };
+        let extent = Point2::new(width + half, height + half);
+        let min = Point2::new(origin.x - extent.x, origin.y - extent.y);
+        let max = Point2::new(origin.x + extent.x, origin.y + extent.y);
+        self.fill(min, max, |p| {
+            let d = p - origin;
+            // distance to the outline, exact for circles
+            let k = ((d.x / width).powi(2) + (d.y / height).powi(2)).sqrt();
+            let radius = match k > 0.0 {
+                true => d.norm() / k,
+                false => width.min(height),
+            };
+            match mode {
+                DrawMode::Fill => k <= 1.0,
+                DrawMode::Line(_) => (d.norm() - radius).abs() <= half,
+            }
+        });
+    }
+    fn draw_line(&mut self, origin: Point2<f32>, target: Point2<f32>, width: f32) {
+        let half = (width / 2.0).max(self.dot_radius());
+        let min = Point2::new(origin.x.min(target.x) - half, origin.y.min(target.y) - half);
+        let max = Point2::new(origin.x.max(target.x) + half, origin.y.max(target.y) + half);
+        self.fill(min, max, |p| segment_distance(p, origin, target) <= half);
+    }
+    fn draw_polygon(&mut self, mode: DrawMode, points: &[Point2<f32>]) {
+        if points.len() < 3 {
+            return;
+        }
+        let half = match mode {
+            DrawMode::Line(w) => (w / 2.0).max(self.dot_radius()),
+            DrawMode::Fill => 0.0,
+        };
+        let mut min = points[0];
+        let mut max = points[0];
+        for p in points.iter() {
+            min = Point2::new(min.x.min(p.x - half), min.y.min(p.y - half));
+            max = Point2::new(max.x.max(p.x + half), max.y.max(p.y + half));
+        }
+        self.fill(min, max, |p| match mode {
+            DrawMode::Fill => polygon_contains(p, points),
+            DrawMode::Line(_) => {
+                let mut j = points.len() - 1;
+                let mut hit = false;
+                for i in 0..points.len() {
+                    hit = hit || segment_distance(p, points[j], points[i]) <= half;
+                    j = i;
+                }
+                hit
+            }
+        });
+    }
+}
+
+impl DrawPrimitives for Terminal {
+    fn transform_stack(&mut self) -> &mut TransformStack {
+        &mut self.transforms
+    }
+    fn set_color(&mut self, color: &Color) {
+        self.color = color.into_rgba();
+    }
+    fn circle(&mut self, mode: DrawMode, origin: Point2<f32>, radius: f32) {
+        self.ellipse(mode, origin, radius, radius);
+    }
+    fn ellipse(&mut self, mode: DrawMode, origin: Point2<f32>, width: f32, height: f32) {
+        let t = self.transforms.current();
+        match t.ellipse(origin, width, height) {
+            MappedEllipse::Ellipse(origin, width, height) => {
+                self.draw_ellipse(t.mode(mode), origin, width, height)
+            }
+            MappedEllipse::Polygon(points) => self.draw_polygon(t.mode(mode), &points),
+        }
+    }
+    fn line(&mut self, origin: Point2<f32>, target: Point2<f32>, width: f32) {
+        let t = self.transforms.current();
+        self.draw_line(t.apply(origin), t.apply(target), t.length(width));
+    }
+    fn text(&mut self, text: &str, origin: Point2<f32>) {
+        let origin = self.transforms.current().apply(origin);
+        let column = (origin.x * self.scale.0 / DOTS_X as f32) as isize;
+        let row = (origin.y * self.scale.1 / DOTS_Y as f32) as isize;
+        if row < 0 || row >= self.rows as isize {
+            return;
+        }
+        for (i, c) in text.chars().enumerate() {
+            let column = column + i as isize;
+            if column >= 0 && column < self.columns as isize {
+                let i = row as usize * self.columns + column as usize;
+                self.letters[i] = Some(c);
+                self.colors[i] = blend(self.background, self.color);
+            }
+        }
+    }
+    fn polygon(&mut self, mode: DrawMode, points: &[Point2<f32>]) {
+        let t = self.transforms.current();
+        self.draw_polygon(t.mode(mode), &t.apply_all(points));
+    }
+}
+
+// Current terminal size as (columns, rows).
+pub fn size() -> Option<(usize, usize)> {
+    let output = Command::new("stty")
+        .arg("size")
+        .stdin(Stdio::inherit())
+        .output()
+        .ok()?;
+    let text = String::from_utf8(output.stdout).ok()?;
+    let mut words = text.split_whitespace();
+    let rows = words.next()?.parse().ok()?;
+    let columns = words.next()?.parse().ok()?;
+    Some((columns, rows))
+}
+
+/// Puts the terminal in raw mode and reads keys on a background thread. The
+/// previous terminal settings and cursor come back when it's dropped.
+pub struct Keyboard {
+    keys: Receiver<Key>,
+    settings: String,
+}
+
+impl Keyboard {
+    pub fn open() -> io::Result<Self> {
+        let output = Command::new("stty")
+            .arg("-g")
+            .stdin(Stdio::inherit())
+            .output()?;
+        let settings = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        stty(&["raw", "-echo"])?;
+        print!("\x1b[?25l\x1b[2J");
+
+        let (sender, keys) = channel();
+        thread::spawn(move || {
+            let stdin = io::stdin();
+            let mut bytes = Vec::new();
+            let mut buffer = [0; 16];
+            while let Ok(n) = stdin.lock().read(&mut buffer) {
+                if n == 0 {
+                    break;
+                }
+                bytes.extend_from_slice(&buffer[..n]);
+                for key in parse_keys(&mut bytes) {
+                    if sender.send(key).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        Ok(Self { keys, settings })
+    }
+
+    // Keys pressed since the last poll.
+    pub fn poll(&self) -> Vec<Key> {
+        self.keys.try_iter().collect()
+    }
+}
+
+impl Drop for Keyboard {
+    fn drop(&mut self) {
+        let _ = stty(&[self.settings.as_str()]);
+        print!("\x1b[0m\x1b[?25h\r\n");
+        let _ = io::stdout().flush();
+    }
+}
+
+fn stty(args: &[&str]) -> io::Result<()> {
+    Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .status()
+        .map(|_| ())
+}
+
+// Takes every complete key out of `bytes`, leaving a trailing partial
+// sequence for the next read.
+fn parse_keys(bytes: &mut Vec<u8>) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let (key, used) = match &bytes[i..] {
+            &[0x1b, b'[', b'A', ..] => (Some(Key::Up), 3),
+            &[0x1b, b'[', b'B', ..] => (Some(Key::Down), 3),
+            &[0x1b, b'[', b'C', ..] => (Some(Key::Right), 3),
+            &[0x1b, b'[', b'D', ..] => (Some(Key::Left), 3),
+            &[0x1b, b'['] => break,
+            &[0x1b, b'[', _, ..] => (None, 3),
+            &[0x1b, ..] => (Some(Key::Escape), 1),
+            &[b'\r', ..] | &[b'\n', ..] => (Some(Key::Enter), 1),
+            &[0x7f, ..] | &[0x08, ..] => (Some(Key::Backspace), 1),
+            // ctrl-c doesn't raise a signal in raw mode
+            &[0x03, ..] => (Some(Key::Escape), 1),
+            &[b, ..] if b < 0x80 => (Some(Key::Char(b as char)), 1),
+            _ => {
+                let valid = match ::std::str::from_utf8(&bytes[i..]) {
+                    Ok(s) => s.len(),
+                    Err(e) => e.valid_up_to(),
+                };
+                let s = ::std::str::from_utf8(&bytes[i..i + valid]).unwrap();
+                match s.chars().next() {
+                    Some(c) => (Some(Key::Char(c)), c.len_utf8()),
+                    // invalid bytes are skipped, a partial character waits
+                    None => match ::std::str::from_utf8(&bytes[i..]) {
+                        Err(e) if e.error_len().is_some() => (None, e.error_len().unwrap()),
+                        _ => break,
+                    },
+                }
+            }
+        };
+        keys.extend(key);
+        i += used;
+    }
+    bytes.drain(..i);
+    keys
+}
+
+#[test]
+fn terminal_draws_braille() {
+    let mut terminal = Terminal::new(4.0, 4.0, 2, 1);
+    terminal.clear(&[0.0, 0.0, 0.0, 1.0]);
+    terminal.set_color(&[1.0, 0.0, 0.0, 1.0]);
+    terminal.line(Point2::new(0.0, 0.5), Point2::new(1.5, 0.5), 0.5);
+    assert_eq!(
+        terminal.frame(),
+        "\x1b[H\x1b[48;2;0;0;0m\x1b[38;2;255;0;0m\u{2809}\x1b[38;2;0;0;0m\u{2800}\r\n\x1b[0m"
+    );
+
+    let mut bytes = vec![b'q', 0x1b, b'[', b'A', 0x1b, b'['];
+    assert_eq!(parse_keys(&mut bytes), vec![Key::Char('q'), Key::Up]);
+    assert_eq!(bytes, vec![0x1b, b'[']);
+}
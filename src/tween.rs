@@ -0,0 +1,360 @@
+//! Values that move from one point to the next over time, with easing.
+//!
+//! ```text
+//! Tween::new(start)
+//!     .to(middle, 0.5, Ease::Out(Family::Back))
+//!     .wait(1.0)
+//!     .to(end, 0.5, Ease::In(Family::Quad))
+//!     .yoyo()
+//!     .looping()
+//! ```
+
+use color;
+use color::Rgba;
+use math::VectorUtils;
+use messages::Message;
+use std::f32::consts::PI;
+
+/// Anything a `Tween` can move. Unlike `VectorUtils::lerp` the amount isn't
+/// clamped, so elastic and back easing can overshoot.
+pub trait Interpolate: Copy {
+    fn interpolate(&self, other: Self, amount: f32) -> Self;
+}
+
+impl<T> Interpolate for T
+where
+    T: VectorUtils + Copy,
+{
+    fn interpolate(&self, other: T, amount: f32) -> T {
+        self.add(other.sub(*self).mul(amount))
+    }
+}
+
+impl Interpolate for Rgba {
+    fn interpolate(&self, other: Rgba, amount: f32) -> Rgba {
+        color::lerp(*self, other, amount)
+    }
+}
+
+/// Values placed along a line, sorted by position and interpolated in
+/// between. Two keys at the same position make a hard step.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keyframes<T> {
+    keys: Vec<(f32, T)>,
+}
+
+impl<T> Keyframes<T>
+where
+    T: Interpolate,
+{
+    pub fn new(mut keys: Vec<(f32, T)>) -> Self {
+        assert!(!keys.is_empty(), "keyframes need at least one key");
+        assert!(
+            keys.iter().all(|key| !key.0.is_nan()),
+            "keyframe positions can't be NaN"
+        );
+        keys.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        Keyframes { keys }
+    }
+
+    pub fn sample(&self, t: f32) -> T {
+        let first = self.keys[0];
+        if t <= first.0 {
+            return first.1;
+        }
+        for pair in self.keys.windows(2) {
+            let ((t0, v0), (t1, v1)) = (pair[0], pair[1]);
+            if t <= t1 && t1 == t0 {
+                return v1;
+            }
+            if t <= t1 {
+                return v0.interpolate(v1, (t - t0) / (t1 - t0));
+            }
+        }
+        self.keys[self.keys.len() - 1].1
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Family {
+    Quad,
+    Cubic,
+    Elastic,
+    Back,
+    Bounce,
+}
+
+impl Family {
+    // Easing in, the other variants are built from it.
+    fn ease_in(self, t: f32) -> f32 {
+        match self {
+            Family::Quad => t * t,
+            Family::Cubic => t * t * t,
+            Family::Elastic => {
+                if t <= 0.0 || t >= 1.0 {
+                    t
+                } else {
+                    -(2.0f32).powf(10.0 * t - 10.0) * ((10.0 * t - 10.75) * 2.0 * PI / 3.0).sin()
+                }
+            }
+            Family::Back => {
+                let c = 1.70158;
+                (c + 1.0) * t * t * t - c * t * t
+            }
+            Family::Bounce => 1.0 - bounce_out(1.0 - t),
+        }
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    let (n, d) = (7.5625, 2.75);
+    if t < 1.0 / d {
+        n * t * t
+    } else if t < 2.0 / d {
+        let t = t - 1.5 / d;
+        n * t * t + 0.75
+    } else if t < 2.5 / d {
+        let t = t - 2.25 / d;
+        n * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / d;
+        n * t * t + 0.984375
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Ease {
+    Linear,
+    In(Family),
+    Out(Family),
+    InOut(Family),
+}
+
+impl Ease {
+    // Maps the time fraction `t` to the fraction of the way covered.
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Ease::Linear => t,
+            Ease::In(family) => family.ease_in(t),
+            Ease::Out(family) => 1.0 - family.ease_in(1.0 - t),
+            Ease::InOut(family) => {
+                if t < 0.5 {
+                    family.ease_in(2.0 * t) / 2.0
+                } else {
+                    1.0 - family.ease_in(2.0 - 2.0 * t) / 2.0
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Step<T> {
+    // `None` holds the previous value.
+    to: Option<T>,
+    duration: f32,
+    ease: Ease,
+}
+
+#[derive(Clone, Debug)]
+pub struct Tween<T> {
+    from: T,
+    steps: Vec<Step<T>>,
+    delay: f32,
+    yoyo: bool,
+    // Times played after the first, `None` forever.
+    repeat: Option<u32>,
+    time: f32,
+    completion: Option<Message>,
+}
+
+impl<T> Tween<T>
+where
+    T: Interpolate,
+{
+    pub fn new(from: T) -> Self {
+        Tween {
+            from,
+            steps: Vec::new(),
+            delay: 0.0,
+            yoyo: false,
+            repeat: Some(0),
+            time: 0.0,
+            completion: None,
+        }
+    }
+
+    // Goes on from wherever the previous step ended.
+    pub fn to(mut self, value: T, duration: f32, ease: Ease) -> Self {
+        self.steps.push(Step {
+            to: Some(value),
+            duration,
+            ease,
+        });
+        self
+    }
+
+    pub fn wait(mut self, duration: f32) -> Self {
+        self.steps.push(Step {
+            to: None,
+            duration,
+            ease: Ease::Linear,
+        });
+        self
+    }
+
+    // Time before the first step starts, not repeated when looping.
+    pub fn delay(mut self, seconds: f32) -> Self {
+        self.delay = seconds;
+        self
+    }
+
+    // Plays every step backwards after the forward pass.
+    pub fn yoyo(mut self) -> Self {
+        self.yoyo = true;
+        self
+    }
+
+    pub fn repeat(mut self, times: u32) -> Self {
+        self.repeat = Some(times);
+        self
+    }
+
+    pub fn looping(mut self) -> Self {
+        self.repeat = None;
+        self
+    }
+
+    // Handed out once by `take_completion` when the tween finishes.
+    pub fn on_complete(mut self, message: Message) -> Self {
+        self.completion = Some(message);
+        self
+    }
+
+    pub fn update(&mut self, delta_time: f32) -> T {
+        self.time += delta_time;
+        self.value()
+    }
+
+    // Jumps to the end, looping tweens to the end of their current cycle.
+    pub fn finish(&mut self) -> T {
+        let cycle = self.cycle();
+        let played = (self.time - self.delay).max(0.0);
+        let cycles = match self.repeat {
+            Some(times) => (times + 1) as f32,
+            None if cycle > 0.0 => (played / cycle).floor() + 1.0,
+            None => 1.0,
+        };
+        self.time = self.delay + cycle * cycles;
+        self.value()
+    }
+
+    pub fn finished(&self) -> bool {
+        match self.repeat {
+            Some(times) => self.time - self.delay >= self.cycle() * (times + 1) as f32,
+            None => false,
+        }
+    }
+
+    pub fn take_completion(&mut self) -> Option<Message> {
+        if self.finished() {
+            self.completion.take()
+        } else {
+            None
+        }
+    }
+
+    pub fn value(&self) -> T {
+        let length = self.length();
+        let cycle = self.cycle();
+        let t = self.time - self.delay;
+        // Finished tweens end where they were going, even zero length ones.
+        let mut local = if self.finished() {
+            cycle
+        } else if t <= 0.0 || cycle <= 0.0 {
+            return self.from;
+        } else {
+            t % cycle
+        };
+        if local > length {
+            local = 2.0 * length - local;
+        }
+        self.at(local)
+    }
+
+    // Length of one forward pass.
+    fn length(&self) -> f32 {
+        self.steps.iter().map(|s| s.duration).sum()
+    }
+
+    fn cycle(&self) -> f32 {
+        match self.yoyo {
+            true => self.length() * 2.0,
+            false => self.length(),
+        }
+    }
+
+    fn at(&self, mut t: f32) -> T {
+        let mut previous = self.from;
+        for step in self.steps.iter() {
+            let to = step.to.unwrap_or(previous);
+            if t < step.duration {
+                return previous.interpolate(to, step.ease.apply(t / step.duration));
+            }
+            t -= step.duration;
+            previous = to;
+        }
+        previous
+    }
+}
+
+#[test]
+fn easing_starts_and_ends_in_place() {
+    let families = [
+        Family::Quad,
+        Family::Cubic,
+        Family::Elastic,
+        Family::Back,
+        Family::Bounce,
+    ];
+    for &family in families.iter() {
+        for &ease in [Ease::In(family), Ease::Out(family), Ease::InOut(family)].iter() {
+            assert!(ease.apply(0.0).abs() < 0.001, "{:?}", ease);
+            assert!((ease.apply(1.0) - 1.0).abs() < 0.001, "{:?}", ease);
+        }
+    }
+    assert!(Ease::Out(Family::Back).apply(0.7) > 1.0);
+}
+
+#[test]
+fn tweens_sequence_yoyo_and_repeat() {
+    let mut tween = Tween::new(0.0f32)
+        .to(10.0, 1.0, Ease::Linear)
+        .wait(1.0)
+        .to(20.0, 1.0, Ease::Linear)
+        .delay(1.0)
+        .yoyo()
+        .repeat(1)
+        .on_complete(Message::Stop);
+    assert_eq!(tween.update(1.5), 5.0);
+    assert_eq!(tween.update(1.0), 10.0);
+    assert_eq!(tween.update(1.0), 15.0);
+    assert_eq!(tween.update(1.5), 10.0);
+    assert!(tween.take_completion().is_none());
+    tween.update(8.0);
+    assert!(tween.finished());
+    assert_eq!(tween.value(), 0.0);
+    assert!(tween.take_completion().is_some());
+    assert!(tween.take_completion().is_none());
+}
+
+#[test]
+fn zero_length_tweens_land_at_once() {
+    let mut tween = Tween::new(0.0f32).to(10.0, 0.0, Ease::Linear);
+    assert!(tween.finished());
+    assert_eq!(tween.value(), 10.0);
+    assert_eq!(tween.finish(), 10.0);
+    let mut delayed = Tween::new(0.0f32).to(10.0, 0.0, Ease::Linear).delay(1.0);
+    assert_eq!(delayed.update(0.5), 0.0);
+    assert_eq!(delayed.update(0.5), 10.0);
+}
@@ -0,0 +1,388 @@
+//! Prototype world kept as a petgraph scene graph, the nodes think for
+//! themselves and change the world through `Mutation`s.
+
+use engine::graphics::{Draw, DrawMode, DrawPrimitives, Transform};
+use engine::logical::{Input, Update};
+use ggez::graphics::Point2;
+use petgraph::stable_graph::{NodeIndex, StableGraph};
+use petgraph::Direction;
+use std::ops::{Index, IndexMut};
+
+/// Nodes hang from `root` in a tree, every edge goes from a parent to one of
+/// its children and carries the child's transform relative to the parent.
+pub struct World {
+    content: StableGraph<Node, Edge>,
+    root: NodeIndex,
+    player_id: NodeIndex,
+}
+
+impl Index<NodeIndex> for World {
+    type Output = Node;
+
+    fn index(&self, id: NodeIndex) -> &Node {
+        &self.content[id]
+    }
+}
+
+impl IndexMut<NodeIndex> for World {
+    fn index_mut(&mut self, id: NodeIndex) -> &mut Node {
+        &mut self.content[id]
+    }
+}
+
+#[derive(Clone)]
+struct Edge {
+    local: Transform,
+}
+
+#[derive(Clone)]
+pub enum Node {
+    Root,
+    Player(Player),
+    Tree(Tree),
+    Monster(Monster),
+    Dead,
+}
+
+/// Changes to the world asked for during an update pass, applied in order
+/// once every node had its turn.
+enum Mutation {
+    Spawn(NodeIndex, Node, Transform),
+    Remove(NodeIndex),
+    Modify(NodeIndex, Box<Fn(&mut Node)>),
+    SetLocal(NodeIndex, Transform),
+    Attach(NodeIndex, NodeIndex),
+}
+
+/// Node logic only gets to read the world, it changes things through
+/// `mutations`.
+trait Think {
+    fn think(&self, id: NodeIndex, world: &World, mutations: &mut Vec<Mutation>);
+}
+
+impl Think for Node {
+    fn think(&self, id: NodeIndex, world: &World, mutations: &mut Vec<Mutation>) {
+        match self {
+            Node::Player(p) => p.think(id, world, mutations),
+            Node::Monster(m) => m.think(id, world, mutations),
+            _ => {},
+        }
+    }
+}
+
+
+#[derive(Clone)]
+pub struct Player {
+    lives: u32,
+}
+
+impl Think for Player {
+    fn think(&self, id: NodeIndex, _world: &World, mutations: &mut Vec<Mutation>) {
+        if self.lives > 1 {
+            mutations.push(Mutation::Modify(id, Box::new(|node| {
+                if let Node::Player(ref mut p) = *node {
+                    p.lives -= 1;
+                }
+            })));
+        } else {
+            mutations.push(Mutation::Modify(id, Box::new(|node| *node = Node::Dead)));
+        }
+    }
+}
+
+impl World {
+    pub fn new() -> Self {
+        let mut content = StableGraph::new();
+        let root = content.add_node(Node::Root);
+        let mut world = Self {
+            content,
+            root,
+            player_id: root,
+        };
+        world.player_id = world.spawn(
+            root,
+            Node::Player(Player { lives: 3 }),
+            Transform::translate(160.0, 200.0),
+        );
+        for i in 0..4 {
+            let x = 40.0 + 80.0 * i as f32;
+            world.spawn(root, Node::Tree(Tree), Transform::translate(x, 60.0));
+        }
+        let monster = world.spawn(
+            root,
+            Node::Monster(Monster),
+            Transform::translate(240.0, 120.0),
+        );
+        world.spawn(monster, Node::Tree(Tree), Transform::translate(0.0, -20.0));
+        world
+    }
+
+    fn spawn(&mut self, parent: NodeIndex, node: Node, local: Transform) -> NodeIndex {
+        let id = self.content.add_node(node);
+        self.content.add_edge(parent, id, Edge { local });
+        id
+    }
+
+    /// Moves `child` under `parent`, keeping its local transform.
+    fn attach(&mut self, parent: NodeIndex, child: NodeIndex) {
+        if child == self.root || self.ancestors(parent).contains(&child) || parent == child {
+            return;
+        }
+        let local = self.local(child).unwrap_or_else(Transform::identity);
+        if let Some(old) = self.parent(child) {
+            let edge = self.content.find_edge(old, child).unwrap();
+            self.content.remove_edge(edge);
+        }
+        self.content.add_edge(parent, child, Edge { local });
+    }
+
+    fn parent(&self, id: NodeIndex) -> Option<NodeIndex> {
+        self.content
+            .neighbors_directed(id, Direction::Incoming)
+            .next()
+    }
+
+    /// Nodes directly under `id`, in no particular order.
+    pub fn children(&self, id: NodeIndex) -> Vec<NodeIndex> {
+        self.content
+            .neighbors_directed(id, Direction::Outgoing)
+            .collect()
+    }
+
+    /// Parent first, up to and including the root.
+    pub fn ancestors(&self, id: NodeIndex) -> Vec<NodeIndex> {
+        let mut ancestors = Vec::new();
+        let mut current = id;
+        while let Some(parent) = self.parent(current) {
+            ancestors.push(parent);
+            current = parent;
+        }
+        ancestors
+    }
+
+    fn descendants(&self, id: NodeIndex) -> Vec<NodeIndex> {
+        let mut descendants = Vec::new();
+        let mut pending = self.children(id);
+        while let Some(child) = pending.pop() {
+            pending.extend(self.children(child));
+            descendants.push(child);
+        }
+        descendants
+    }
+
+    fn local(&self, id: NodeIndex) -> Option<Transform> {
+        let parent = self.parent(id)?;
+        let edge = self.content.find_edge(parent, id)?;
+        self.content.edge_weight(edge).map(|e| e.local)
+    }
+
+    fn local_mut(&mut self, id: NodeIndex) -> Option<&mut Transform> {
+        let parent = self.parent(id)?;
+        let edge = self.content.find_edge(parent, id)?;
+        self.content.edge_weight_mut(edge).map(|e| &mut e.local)
+    }
+
+    fn world_transform(&self, id: NodeIndex) -> Transform {
+        let mut transform = self.local(id).unwrap_or_else(Transform::identity);
+        for ancestor in self.ancestors(id) {
+            if let Some(local) = self.local(ancestor) {
+                transform = local.then(&transform);
+            }
+        }
+        transform
+    }
+
+    fn is_alive(&self, id: NodeIndex) -> bool {
+        match self.content.node_weight(id) {
+            Some(&Node::Dead) | None => false,
+            _ => true,
+        }
+    }
+
+    fn apply(&mut self, mutations: Vec<Mutation>) {
+        for mutation in mutations {
+            match mutation {
+                // Earlier mutations may have removed the nodes involved.
+                Mutation::Spawn(parent, node, local) => {
+                    if self.content.contains_node(parent) {
+                        self.spawn(parent, node, local);
+                    }
+                }
+                Mutation::Remove(id) => self.destroy(id),
+                Mutation::Modify(id, modify) => {
+                    if let Some(node) = self.content.node_weight_mut(id) {
+                        modify(node);
+                    }
+                }
+                Mutation::SetLocal(id, transform) => {
+                    if let Some(local) = self.local_mut(id) {
+                        *local = transform;
+                    }
+                }
+                Mutation::Attach(parent, child) => {
+                    if self.content.contains_node(parent) && self.content.contains_node(child) {
+                        self.attach(parent, child);
+                    }
+                }
+            }
+        }
+        let dead: Vec<NodeIndex> = self
+            .content
+            .node_indices()
+            .filter(|id| !self.is_alive(*id))
+            .collect();
+        for id in dead {
+            if self.content.contains_node(id) {
+                self.destroy(id);
+            }
+        }
+    }
+
+    /// Removes the node together with everything attached to it.
+    fn destroy(&mut self, id: NodeIndex) {
+        if id == self.root {
+            return;
+        }
+        for descendant in self.descendants(id) {
+            self.content.remove_node(descendant);
+        }
+        self.content.remove_node(id);
+    }
+}
+
+impl<S> Draw<S> for World
+where
+    S: DrawPrimitives,
+{
+    fn draw(&self, surface: &mut S) {
+        surface.clear(&[0.0, 0.0, 0.0, 1.0]);
+        for id in self.content.node_indices() {
+            let (color, radius) = match self[id] {
+                Node::Player(_) => ([0.3, 0.6, 1.0, 1.0], 8.0),
+                Node::Tree(_) => ([0.2, 0.8, 0.3, 1.0], 6.0),
+                Node::Monster(_) => ([1.0, 0.3, 0.2, 1.0], 10.0),
+                _ => continue,
+            };
+            surface.push_transform(self.world_transform(id));
+            surface.set_color(&color);
+            surface.circle(DrawMode::Fill, Point2::origin(), radius);
+            surface.pop_transform();
+        }
+    }
+}
+
+impl<B> Update<B> for World
+where
+    B: Sized,  // Here we can specify backend capabilities
+{
+    fn update(&mut self, _backend: &mut B) {
+        let mut mutations = Vec::new();
+        for id in self.content.node_indices() {
+            self[id].think(id, self, &mut mutations);
+        }
+        self.apply(mutations);
+    }
+}
+
+#[derive(Clone)]
+pub struct Tree;
+
+#[derive(Clone)]
+pub struct Monster;
+
+// Distance at which a monster grabs the player and gets carried along.
+const MONSTER_REACH: f32 = 20.0;
+const MONSTER_SPEED: f32 = 1.0;
+
+impl Think for Monster {
+    fn think(&self, id: NodeIndex, world: &World, mutations: &mut Vec<Mutation>) {
+        let player = world.player_id;
+        if !world.is_alive(player) || world.ancestors(id).contains(&player) {
+            return;
+        }
+        let origin = Point2::origin();
+        let position = world.world_transform(id).apply(origin);
+        let target = world.world_transform(player).apply(origin);
+        let distance = (target - position).norm();
+        if distance < MONSTER_REACH {
+            mutations.push(Mutation::Attach(player, id));
+            let offset = world.world_transform(player).inverse().apply(position);
+            mutations.push(Mutation::SetLocal(id, Transform::translate(offset.x, offset.y)));
+        } else if let Some(local) = world.local(id) {
+            let step = (target - position) * (MONSTER_SPEED / distance);
+            let moved = Transform::translate(step.x, step.y).then(&local);
+            mutations.push(Mutation::SetLocal(id, moved));
+        }
+    }
+}
+
+// Nothing in the prototype listens to keys yet.
+impl<K> Input<K> for World {
+    fn key_down(&mut self, _key: K) {}
+    fn key_up(&mut self, _key: K) {}
+}
+
+#[test]
+fn world_scene_graph() {
+    let mut world = World::new();
+    let monster = world
+        .content
+        .node_indices()
+        .find(|id| match world[*id] {
+            Node::Monster(_) => true,
+            _ => false,
+        })
+        .unwrap();
+    let rider = world.children(monster)[0];
+    assert_eq!(world.ancestors(rider), vec![monster, world.root]);
+    assert_eq!(
+        world.world_transform(rider).apply(Point2::origin()),
+        Point2::new(240.0, 100.0)
+    );
+
+    world.local_mut(monster).unwrap().tx += 10.0;
+    assert_eq!(
+        world.world_transform(rider).apply(Point2::origin()),
+        Point2::new(250.0, 100.0)
+    );
+
+    let player = world.player_id;
+    world.attach(player, monster);
+    assert_eq!(world.ancestors(rider), vec![monster, player, world.root]);
+
+    world.destroy(player);
+    assert!(!world.content.contains_node(monster));
+    assert!(!world.content.contains_node(rider));
+    assert_eq!(world.content.node_count(), 5);
+}
+
+#[test]
+fn world_update_applies_mutations() {
+    let mut world = World::new();
+    let player = world.player_id;
+    let monster = world
+        .content
+        .node_indices()
+        .find(|id| match world[*id] {
+            Node::Monster(_) => true,
+            _ => false,
+        })
+        .unwrap();
+    let distance = |world: &World| {
+        let origin = Point2::origin();
+        let p = world.world_transform(player).apply(origin);
+        (world.world_transform(monster).apply(origin) - p).norm()
+    };
+    let before = distance(&world);
+    world.update(&mut ());
+    assert!(distance(&world) < before);
+    match world[player] {
+        Node::Player(ref p) => assert_eq!(p.lives, 2),
+        _ => panic!("the player should still be around"),
+    }
+
+    world.update(&mut ());
+    world.update(&mut ());
+    assert!(!world.content.contains_node(player));
+    world.update(&mut ());
+}